@@ -27,7 +27,7 @@ async fn main() -> jup_ag::Result<()> {
             output_token,
             ui_amount_to_amount(ui_amount, 9),
             QuoteConfig {
-                only_direct_routes,
+                only_direct_routes: Some(only_direct_routes),
                 slippage_bps: Some(slippage_bps),
                 ..QuoteConfig::default()
             },
@@ -58,7 +58,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         msol,
         ui_amount_to_amount(0.01, 9),
         QuoteConfig {
-            only_direct_routes,
+            only_direct_routes: Some(only_direct_routes),
             slippage_bps: Some(slippage_bps),
             ..QuoteConfig::default()
         },
@@ -1,6 +1,5 @@
 use jup_ag::{QuoteConfig, SwapRequest};
 use solana_sdk::{pubkey, signature::Keypair, signature::Signer};
-use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -14,9 +13,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let quotes = jup_ag::quote(
         sol,
         msol,
-        ui_amount_to_amount(0.01, 9),
+        jup_ag::to_raw_amount(&sol, 0.01).await?,
         QuoteConfig {
-            only_direct_routes,
+            only_direct_routes: Some(only_direct_routes),
             slippage_bps: Some(slippage_bps),
             ..QuoteConfig::default()
         },
@@ -30,10 +29,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|| "Unknown DEX".to_string());
     println!(
         "Quote: {} SOL for {} mSOL via {} (worst case with slippage: {}). Impact: {:.2}%",
-        amount_to_ui_amount(quotes.in_amount, 9),
-        amount_to_ui_amount(quotes.out_amount, 9),
+        jup_ag::to_ui_amount(&sol, quotes.in_amount).await?,
+        jup_ag::to_ui_amount(&msol, quotes.out_amount).await?,
         route,
-        amount_to_ui_amount(quotes.other_amount_threshold, 9),
+        jup_ag::to_ui_amount(&msol, quotes.other_amount_threshold).await?,
         quotes.price_impact_pct * 100.
     );
 
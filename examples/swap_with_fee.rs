@@ -0,0 +1,56 @@
+use jup_ag::{QuoteConfig, SwapRequest};
+use solana_sdk::{pubkey, pubkey::Pubkey, signature::Keypair, signature::Signer};
+use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let sol = pubkey!("So11111111111111111111111111111111111111112");
+    let msol = pubkey!("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So");
+
+    // Your Jupiter referral account, see https://referral.jup.ag/
+    let referral_account = std::env::var("REFERRAL_ACCOUNT")
+        .map_err(|_| "Set REFERRAL_ACCOUNT to your Jupiter referral account")?
+        .parse::<Pubkey>()?;
+
+    let keypair = Keypair::new();
+
+    let platform_fee_bps = 50;
+    let quotes = jup_ag::quote(
+        sol,
+        msol,
+        ui_amount_to_amount(0.01, 9),
+        QuoteConfig {
+            slippage_bps: Some(100),
+            platform_fee_bps: Some(platform_fee_bps),
+            ..QuoteConfig::default()
+        },
+    )
+    .await?;
+
+    println!(
+        "Quote: {} SOL for {} mSOL",
+        amount_to_ui_amount(quotes.in_amount, 9),
+        amount_to_ui_amount(quotes.out_amount, 9),
+    );
+    match &quotes.platform_fee {
+        Some(platform_fee) => println!(
+            "Platform fee: {} mSOL ({} bps)",
+            amount_to_ui_amount(platform_fee.amount, 9),
+            platform_fee.fee_bps
+        ),
+        None => println!("No platform fee in quote"),
+    }
+
+    // The fee is taken in the output mint for ExactIn swaps
+    let request = SwapRequest::new(keypair.pubkey(), quotes.clone())
+        .with_referral(&referral_account, &quotes.output_mint);
+    println!("Fee account: {}", request.fee_account.expect("fee account"));
+
+    let swap = jup_ag::swap(request).await?;
+    println!(
+        "Swap transaction: {:?}",
+        swap.swap_transaction.message.static_account_keys()
+    );
+
+    Ok(())
+}
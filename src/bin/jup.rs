@@ -0,0 +1,162 @@
+//! Command-line access to the Jupiter APIs, enabled by the `cli` feature
+use {
+    jup_ag::{QuoteConfig, SwapRequest, Token},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Signer},
+    },
+    std::{env, error::Error, process::exit},
+};
+
+const USAGE: &str = "\
+Usage:
+  jup quote <INPUT> <OUTPUT> <UI_AMOUNT> [--slippage-bps <BPS>]
+  jup swap <INPUT> <OUTPUT> <UI_AMOUNT> --keypair <FILE> [--slippage-bps <BPS>]
+  jup price <INPUT> <OUTPUT> [<UI_AMOUNT>]
+  jup tokens
+
+Tokens are given by mint address or symbol. `swap` sends the transaction through RPC_URL.";
+
+/// Resolves a mint address or token symbol through the token list
+async fn resolve_token(token: &str) -> jup_ag::Result<&'static Token> {
+    let token_list = jup_ag::cached_token_list().await?;
+    match token.parse::<Pubkey>() {
+        Ok(mint) => token_list
+            .by_mint(&mint)
+            .ok_or(jup_ag::Error::UnknownMint(mint)),
+        Err(_) => token_list.resolve_symbol(token),
+    }
+}
+
+struct Args {
+    positional: Vec<String>,
+    slippage_bps: Option<u64>,
+    keypair: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        positional: vec![],
+        slippage_bps: None,
+        keypair: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut next_value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--slippage-bps" => {
+                let value = next_value()?;
+                parsed.slippage_bps = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid --slippage-bps `{value}`"))?,
+                );
+            }
+            "--keypair" => parsed.keypair = Some(next_value()?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => parsed.positional.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_ui_amount(ui_amount: &str) -> Result<f64, String> {
+    ui_amount
+        .parse()
+        .map_err(|_| format!("invalid amount `{ui_amount}`"))
+}
+
+async fn quote(
+    input: &str,
+    output: &str,
+    ui_amount: &str,
+    slippage_bps: Option<u64>,
+) -> Result<jup_ag::Quote, Box<dyn Error>> {
+    let input_token = resolve_token(input).await?;
+    let output_token = resolve_token(output).await?;
+    let quote = jup_ag::quote_ui(
+        input_token.address,
+        output_token.address,
+        parse_ui_amount(ui_amount)?,
+        QuoteConfig {
+            slippage_bps,
+            ..QuoteConfig::default()
+        },
+    )
+    .await?;
+
+    println!(
+        "{}",
+        quote.summary_with_decimals(input_token.decimals, output_token.decimals)
+    );
+    println!("{}", quote.render_route());
+    Ok(quote)
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let positional = args
+        .positional
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    match positional[..] {
+        ["quote", input, output, ui_amount] => {
+            quote(input, output, ui_amount, args.slippage_bps).await?;
+        }
+        ["swap", input, output, ui_amount] => {
+            let keypair_file = args.keypair.ok_or("swap requires --keypair <FILE>")?;
+            let keypair = read_keypair_file(&keypair_file)
+                .map_err(|err| format!("read keypair {keypair_file}: {err}"))?;
+
+            let quote = quote(input, output, ui_amount, args.slippage_bps).await?;
+            let swap = jup_ag::swap(SwapRequest::new(keypair.pubkey(), quote)).await?;
+
+            let recent_blockhash = *swap.swap_transaction.message.recent_blockhash();
+            let transaction = jup_ag::rpc::sign_transaction(
+                swap.swap_transaction,
+                recent_blockhash,
+                &[&keypair],
+            )?;
+            let send_config = jup_ag::rpc::SendConfig::default();
+            let rpc_url = send_config.url.clone();
+            let signature = jup_ag::rpc::send_transaction(&transaction, send_config).await?;
+            println!("Sent {signature}");
+            jup_ag::rpc::confirm_swap(&rpc_url, &signature, swap.last_valid_block_height).await?;
+            println!("Confirmed");
+        }
+        ["price", input, output] | ["price", input, output, _] => {
+            let ui_amount = positional.get(3).copied().unwrap_or("1");
+            let input_token = resolve_token(input).await?;
+            let output_token = resolve_token(output).await?;
+            let price = jup_ag::price(
+                input_token.address,
+                output_token.address,
+                parse_ui_amount(ui_amount)?,
+            )
+            .await?;
+            println!("{price}");
+        }
+        ["tokens"] => {
+            for token in jup_ag::cached_token_list().await?.tokens() {
+                println!(
+                    "{:<10} {} ({} decimals)",
+                    token.symbol, token.address, token.decimals
+                );
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    let result = match parse_args(env::args().skip(1)) {
+        Ok(args) => run(args).await,
+        Err(err) => Err(format!("{err}\n\n{USAGE}").into()),
+    };
+    if let Err(err) = result {
+        eprintln!("{err}");
+        exit(1);
+    }
+}
@@ -16,9 +16,9 @@ pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
 where
     T: FromStr,
     D: Deserializer<'de>,
-    <T as FromStr>::Err: std::fmt::Debug,
+    <T as FromStr>::Err: std::fmt::Display,
 {
     let s: String = String::deserialize(deserializer)?;
     s.parse()
-        .map_err(|e| de::Error::custom(format!("Parse error: {e:?}")))
+        .map_err(|e| de::Error::custom(format!("Parse error for `{s}`: {e}")))
 }
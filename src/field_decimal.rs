@@ -0,0 +1,65 @@
+// Parse a `Decimal` sent either as a JSON number or as a string from its exact text, as a JSON
+// number would otherwise be rounded through `f64` first. The `Decimal` accompanies the `f64`
+// parsed from the same text, so enabling the `rust_decimal` feature only adds to the API.
+use {
+    rust_decimal::Decimal,
+    serde::{de, Deserialize, Deserializer},
+    serde_json::value::RawValue,
+    std::collections::BTreeMap,
+};
+
+/// The text of a JSON number or string, without the quotes of a string
+fn text<E: de::Error>(raw: &RawValue) -> Result<String, E> {
+    let text = raw.get();
+    if text.starts_with('"') {
+        serde_json::from_str::<String>(text).map_err(de::Error::custom)
+    } else {
+        Ok(text.to_string())
+    }
+}
+
+fn parse<E: de::Error>(s: &str) -> Result<Decimal, E> {
+    if s.contains(['e', 'E']) {
+        Decimal::from_scientific(s)
+    } else {
+        Decimal::from_str_exact(s)
+    }
+    .map_err(|e| de::Error::custom(format!("Parse error for `{s}`: {e:?}")))
+}
+
+/// A number as both an `f64` and an exact `Decimal`
+pub struct Exact {
+    pub value: f64,
+    pub decimal: Decimal,
+}
+
+impl<'de> Deserialize<'de> for Exact {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = text(&Box::<RawValue>::deserialize(deserializer)?)?;
+        Ok(Self {
+            value: s
+                .parse()
+                .map_err(|e| de::Error::custom(format!("Parse error for `{s}`: {e}")))?,
+            decimal: parse(&s)?,
+        })
+    }
+}
+
+/// Deserializes a JSON object with `deserialize`, along with the exact `Decimal` of its `key`
+/// field if it has one
+pub fn with_exact<'de, D, T>(
+    deserializer: D,
+    key: &str,
+    deserialize: impl FnOnce(&str) -> serde_json::Result<T>,
+) -> Result<(T, Option<Decimal>), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let fields = BTreeMap::<String, Box<RawValue>>::deserialize(deserializer)?;
+    let decimal = fields.get(key).map(|raw| parse(&text(raw)?)).transpose()?;
+    let json = serde_json::to_string(&fields).map_err(de::Error::custom)?;
+    Ok((deserialize(&json).map_err(de::Error::custom)?, decimal))
+}
@@ -1,10 +1,46 @@
-// Deserialize Instruction with a custom function
+// (De)serialize Instruction with a custom function
 pub mod instruction {
     use base64::prelude::{Engine as _, BASE64_STANDARD};
-    use serde::{Deserialize, Deserializer};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use solana_sdk::{instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey};
     use std::str::FromStr;
 
+    pub fn serialize<S>(instruction: &Instruction, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct InstructionFields {
+            program_id: String,
+            accounts: Vec<AccountMetaFields>,
+            data: String,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AccountMetaFields {
+            pubkey: String,
+            is_signer: bool,
+            is_writable: bool,
+        }
+
+        InstructionFields {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|acc| AccountMetaFields {
+                    pubkey: acc.pubkey.to_string(),
+                    is_signer: acc.is_signer,
+                    is_writable: acc.is_writable,
+                })
+                .collect(),
+            data: BASE64_STANDARD.encode(&instruction.data),
+        }
+        .serialize(serializer)
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Instruction, D::Error>
     where
         D: Deserializer<'de>,
@@ -56,11 +92,23 @@ pub mod instruction {
     }
 }
 
-// Deserialize Option<Instruction> with a custom function
+// (De)serialize Option<Instruction> with a custom function
 pub mod option_instruction {
-    use serde::{Deserialize, Deserializer};
+    use serde::{Deserialize, Deserializer, Serializer};
     use solana_sdk::instruction::Instruction;
 
+    pub fn serialize<S>(instruction: &Option<Instruction>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match instruction {
+            Some(instruction) => {
+                crate::field_instruction::instruction::serialize(instruction, serializer)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Instruction>, D::Error>
     where
         D: Deserializer<'de>,
@@ -81,11 +129,18 @@ pub mod option_instruction {
     }
 }
 
-// Deserialize Vec<Instruction> with a custom function
+// (De)serialize Vec<Instruction> with a custom function
 pub mod vec_instruction {
-    use serde::{Deserialize, Deserializer};
+    use serde::{Deserialize, Deserializer, Serializer};
     use solana_sdk::instruction::Instruction;
 
+    pub fn serialize<S>(instructions: &[Instruction], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(instructions.iter().map(crate::field_instruction::Wrapper))
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Instruction>, D::Error>
     where
         D: Deserializer<'de>,
@@ -104,3 +159,15 @@ pub mod vec_instruction {
         Ok(instructions)
     }
 }
+
+// Serializes a borrowed Instruction through `instruction::serialize`, for sequences
+struct Wrapper<'a>(&'a solana_sdk::instruction::Instruction);
+
+impl serde::Serialize for Wrapper<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        instruction::serialize(self.0, serializer)
+    }
+}
@@ -0,0 +1,78 @@
+// Deserialize a value sent either as a JSON number or as a string
+use {
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::str::FromStr,
+};
+
+// A JSON number is kept as a `serde_json::Number` and parsed from its text like a string, so a
+// number out of range for `T` gets the same error as a string would
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(serde_json::Number),
+    String(String),
+}
+
+impl NumberOrString {
+    fn parse<T, E>(self) -> Result<T, E>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: std::fmt::Display,
+        E: de::Error,
+    {
+        let s = match self {
+            // `f64`'s `Display` never uses an exponent, which integer types cannot parse
+            Self::Number(n) => match n.as_f64() {
+                Some(f) if n.is_f64() => f.to_string(),
+                _ => n.to_string(),
+            },
+            Self::String(s) => s,
+        };
+        s.parse()
+            .map_err(|e| de::Error::custom(format!("Parse error for `{s}`: {e}")))
+    }
+}
+
+pub fn serialize<T, S>(t: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    t.serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    D: Deserializer<'de>,
+    <T as FromStr>::Err: std::fmt::Display,
+{
+    NumberOrString::deserialize(deserializer)?.parse()
+}
+
+pub mod option {
+    use {
+        super::NumberOrString,
+        serde::{Deserialize, Deserializer, Serialize, Serializer},
+        std::str::FromStr,
+    };
+
+    pub fn serialize<T, S>(t: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        t.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        D: Deserializer<'de>,
+        <T as FromStr>::Err: std::fmt::Display,
+    {
+        Option::<NumberOrString>::deserialize(deserializer)?
+            .map(NumberOrString::parse)
+            .transpose()
+    }
+}
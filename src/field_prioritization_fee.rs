@@ -1,4 +1,8 @@
-use {crate::PrioritizationFeeLamports, serde::Serialize, serde::Serializer};
+use {
+    crate::{PrioritizationFeeLamports, PriorityLevel},
+    serde::Serialize,
+    serde::Serializer,
+};
 
 pub fn serialize<S>(
     prioritization_fee_lamports: &PrioritizationFeeLamports,
@@ -7,8 +11,32 @@ pub fn serialize<S>(
 where
     S: Serializer,
 {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    enum PrioritizationFee<'a> {
+        #[serde(rename_all = "camelCase")]
+        PriorityLevelWithMaxLamports {
+            priority_level: &'a PriorityLevel,
+            max_lamports: u64,
+        },
+        JitoTipLamports(u64),
+    }
+
     match prioritization_fee_lamports {
         PrioritizationFeeLamports::Auto => "auto".serialize(serializer),
         PrioritizationFeeLamports::Exact { lamports } => lamports.serialize(serializer),
+        // Sent as `computeUnitPriceMicroLamports` instead, see `SwapRequest::body()`
+        PrioritizationFeeLamports::ComputeUnitPrice { .. } => serializer.serialize_none(),
+        PrioritizationFeeLamports::PriorityLevelWithMaxLamports {
+            priority_level,
+            max_lamports,
+        } => PrioritizationFee::PriorityLevelWithMaxLamports {
+            priority_level,
+            max_lamports: *max_lamports,
+        }
+        .serialize(serializer),
+        PrioritizationFeeLamports::JitoTipLamports { lamports } => {
+            PrioritizationFee::JitoTipLamports(*lamports).serialize(serializer)
+        }
     }
 }
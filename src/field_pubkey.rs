@@ -19,7 +19,6 @@ pub mod vec {
         Ok(vec_pubkey)
     }
 
-    #[allow(dead_code)]
     pub fn serialize<S>(vec_pubkey: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -31,10 +30,20 @@ pub mod vec {
 
 pub mod option {
     use {
-        serde::{Serialize, Serializer},
+        serde::{de, Deserialize, Deserializer, Serialize, Serializer},
         solana_sdk::pubkey::Pubkey,
+        std::str::FromStr,
     };
 
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Pubkey>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| Pubkey::from_str(&s).map_err(de::Error::custom))
+            .transpose()
+    }
+
     pub fn serialize<S>(t: &Option<Pubkey>, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -1,19 +1,34 @@
 use {
     base64::prelude::{Engine as _, BASE64_STANDARD},
+    futures_util::{Stream, StreamExt},
     itertools::Itertools,
     serde::{Deserialize, Serialize},
     solana_sdk::transaction::VersionedTransaction,
     solana_sdk::{
-        instruction::Instruction,
+        hash::Hash,
+        instruction::{CompiledInstruction, Instruction},
+        message::VersionedMessage,
         pubkey::{ParsePubkeyError, Pubkey},
+        signature::Signature,
+        signer::Signer,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        env, fmt,
+        str::FromStr,
+        time::Duration,
     },
-    std::{collections::HashMap, env, fmt, str::FromStr},
 };
 
 mod field_as_string;
+#[cfg(feature = "rust_decimal")]
+mod field_decimal;
 mod field_instruction;
+mod field_number_or_string;
 mod field_prioritization_fee;
 mod field_pubkey;
+#[cfg(feature = "rpc")]
+pub mod rpc;
 
 /// A `Result` alias where the `Err` case is `jup_ag::Error`.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -28,8 +43,19 @@ fn price_api_url() -> String {
     env::var("PRICE_API_URL").unwrap_or_else(|_| "https://price.jup.ag/v1".to_string())
 }
 
+// Reference: https://station.jup.ag/docs/apis/price-api-v2
+fn price_v2_api_url() -> String {
+    env::var("PRICE_V2_API_URL").unwrap_or_else(|_| "https://api.jup.ag/price/v2".to_string())
+}
+
+// Reference: https://station.jup.ag/docs/token-list/token-list-api
+fn tokens_api_url() -> String {
+    env::var("TOKENS_API_URL").unwrap_or_else(|_| "https://tokens.jup.ag".to_string())
+}
+
 /// The Errors that may occur while using this crate
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("reqwest: {0}")]
     Reqwest(#[from] reqwest::Error),
@@ -43,18 +69,91 @@ pub enum Error {
     #[error("bincode: {0}")]
     Bincode(#[from] bincode::Error),
 
-    #[error("Jupiter API: {0}")]
-    JupiterApi(String),
+    /// An error reported by the Jupiter API, with the HTTP `status` when the response was not a
+    /// success and the `errorCode` when it had one
+    #[error(
+        "Jupiter API{}: {message}{}",
+        .status.map(|status| format!(" ({status})")).unwrap_or_default(),
+        .code.as_ref().map(|code| format!(" ({code})")).unwrap_or_default(),
+    )]
+    JupiterApi {
+        status: Option<reqwest::StatusCode>,
+        message: String,
+        code: Option<JupiterErrorCode>,
+    },
 
     #[error("serde_json: {0}")]
     SerdeJson(#[from] serde_json::Error),
 
+    #[error("empty response body from {endpoint}")]
+    EmptyResponse { endpoint: String },
+
+    #[error("blocking task: {0}")]
+    BlockingTask(#[from] tokio::task::JoinError),
+
     #[error("parse SwapMode: Invalid value `{value}`")]
     ParseSwapMode { value: String },
+
+    #[error("parse PriorityLevel: Invalid value `{value}`")]
+    ParsePriorityLevel { value: String },
+
+    #[error("parse PrioritizationFeeLamports: Invalid value `{value}`, expected `auto`, `exact:<lamports>`, `cu_price:<micro lamports>`, `jito:<lamports>` or `level:<medium|high|veryHigh>:<max lamports>`")]
+    ParsePrioritizationFeeLamports { value: String },
+
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error("signer: {0}")]
+    Signer(#[from] solana_sdk::signer::SignerError),
+
+    #[error("unknown token symbol `{0}`")]
+    UnknownToken(String),
+
+    #[error("mint {0} is not in the token list")]
+    UnknownMint(Pubkey),
+
+    #[error("ambiguous token symbol `{symbol}`, candidates: {}", .candidates.iter().join(", "))]
+    AmbiguousToken {
+        symbol: String,
+        candidates: Vec<Pubkey>,
+    },
+
+    #[error("unknown fields in response: {}", .0.join(", "))]
+    UnknownFields(Vec<String>),
+
+    #[error("quoted route does not pass through any of the intermediate tokens")]
+    IntermediateTokenNotInRoute,
+
+    #[error("deadline exceeded")]
+    DeadlineExceeded,
+
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("transaction of {size} bytes exceeds the {MAX_TRANSACTION_SIZE} byte limit")]
+    TransactionTooLarge { size: usize },
+
+    #[error("decode swap transaction: {source}")]
+    TransactionDecode { source: Box<Error> },
+
+    #[cfg(feature = "cancellation")]
+    #[error("cancelled")]
+    Cancelled,
+
+    #[cfg(feature = "rpc")]
+    #[error("RPC: {0}")]
+    Rpc(String),
+
+    #[cfg(feature = "rpc")]
+    #[error("transaction {signature} expired before it was confirmed")]
+    Expired {
+        signature: solana_sdk::signature::Signature,
+    },
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "rust_decimal", serde(remote = "Self"))]
 pub struct Price {
     #[serde(with = "field_as_string", rename = "id")]
     pub input_mint: Pubkey,
@@ -64,11 +163,43 @@ pub struct Price {
     pub output_mint: Pubkey,
     #[serde(rename = "vsTokenSymbol")]
     pub output_symbol: String,
+    /// Whole `output_mint` tokens per whole `input_mint` token. Both mints' decimals are already
+    /// applied by the API, so no scaling is needed for any vsToken.
     pub price: f64,
+    /// `price` parsed exactly from the response, enabled by the `rust_decimal` feature
+    #[cfg(feature = "rust_decimal")]
+    #[serde(skip)]
+    pub price_decimal: rust_decimal::Decimal,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg(feature = "rust_decimal")]
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (mut price, price_decimal) =
+            field_decimal::with_exact(deserializer, "price", |json| {
+                Price::deserialize(&mut serde_json::Deserializer::from_str(json))
+            })?;
+        price.price_decimal = price_decimal.unwrap_or_default();
+        Ok(price)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl Serialize for Price {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Price::serialize(self, serializer)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "rust_decimal", serde(remote = "Self"))]
 pub struct Quote {
     #[serde(with = "field_as_string")]
     pub input_mint: Pubkey,
@@ -86,26 +217,401 @@ pub struct Quote {
     #[serde(with = "field_as_string")]
     pub price_impact_pct: f64,
     pub route_plan: Vec<RoutePlan>,
+    #[serde(default)]
     pub context_slot: Option<u64>,
+    #[serde(default)]
     pub time_taken: Option<f64>,
+    /// Routing quality signals, when the API includes them
+    pub score_report: Option<serde_json::Value>,
+    /// USD value of `in_amount`, when the API includes it
+    #[serde(default, with = "field_number_or_string::option")]
+    pub in_usd_value: Option<f64>,
+    /// USD value of `out_amount`, when the API includes it
+    #[serde(default, with = "field_number_or_string::option")]
+    pub out_usd_value: Option<f64>,
+    /// USD value of the swap, when the API includes it
+    #[serde(default, with = "field_number_or_string::option")]
+    pub swap_usd_value: Option<f64>,
+    /// Decimals of `input_mint`, when the API includes them
+    #[serde(default)]
+    pub input_decimals: Option<u8>,
+    /// Decimals of `output_mint`, when the API includes them
+    #[serde(default)]
+    pub output_decimals: Option<u8>,
+    /// Set on quotes from `estimate_for_amount()`, which were scaled locally rather than
+    /// returned by the API
+    #[serde(skip)]
+    pub is_estimate: bool,
+    /// `price_impact_pct` parsed exactly from its decimal string, enabled by the `rust_decimal`
+    /// feature
+    #[cfg(feature = "rust_decimal")]
+    #[serde(skip)]
+    pub price_impact_pct_decimal: rust_decimal::Decimal,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg(feature = "rust_decimal")]
+impl<'de> Deserialize<'de> for Quote {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (mut quote, price_impact_pct_decimal) =
+            field_decimal::with_exact(deserializer, "priceImpactPct", |json| {
+                Quote::deserialize(&mut serde_json::Deserializer::from_str(json))
+            })?;
+        quote.price_impact_pct_decimal = price_impact_pct_decimal.unwrap_or_default();
+        Ok(quote)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl Serialize for Quote {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Quote::serialize(self, serializer)
+    }
+}
+
+impl Quote {
+    fn first_label(&self) -> &str {
+        self.route_plan
+            .first()
+            .and_then(|route_plan| route_plan.swap_info.label.as_deref())
+            .unwrap_or("Unknown DEX")
+    }
+
+    /// Non-fatal issues with the quote that may warrant confirmation before swapping
+    pub fn warnings(&self, max_price_impact_pct: f64) -> Vec<QuoteWarning> {
+        let mut warnings = vec![];
+        let price_impact_pct = self.price_impact_pct;
+        if price_impact_pct > max_price_impact_pct {
+            warnings.push(QuoteWarning::HighPriceImpact { price_impact_pct });
+        }
+
+        // The legs of a split share an input mint, and their percents should total 100
+        let mut percent_sums: Vec<(Pubkey, u32)> = vec![];
+        for route_plan in &self.route_plan {
+            let input_mint = route_plan.swap_info.input_mint;
+            match percent_sums
+                .iter_mut()
+                .find(|(mint, _)| *mint == input_mint)
+            {
+                Some((_, percent_sum)) => *percent_sum += route_plan.percent as u32,
+                None => percent_sums.push((input_mint, route_plan.percent as u32)),
+            }
+        }
+        warnings.extend(
+            percent_sums
+                .into_iter()
+                .filter(|(_, percent_sum)| *percent_sum != 100)
+                .map(
+                    |(input_mint, percent_sum)| QuoteWarning::RoutePercentMismatch {
+                        input_mint,
+                        percent_sum,
+                    },
+                ),
+        );
+        warnings
+    }
+
+    /// Whether `in_amount` is fixed and `out_amount` is estimated
+    pub fn is_exact_in(&self) -> bool {
+        matches!(self.swap_mode.parse(), Ok(SwapMode::ExactIn))
+    }
+
+    /// Whether `out_amount` is fixed and `in_amount` is estimated
+    pub fn is_exact_out(&self) -> bool {
+        matches!(self.swap_mode.parse(), Ok(SwapMode::ExactOut))
+    }
+
+    /// Minimum output amount after slippage. Only set for `ExactIn` quotes, where
+    /// `other_amount_threshold` bounds the output
+    pub fn worst_case_out(&self) -> Option<u64> {
+        self.is_exact_in().then_some(self.other_amount_threshold)
+    }
+
+    /// Maximum input amount after slippage. Only set for `ExactOut` quotes, where
+    /// `other_amount_threshold` bounds the input
+    pub fn worst_case_in(&self) -> Option<u64> {
+        self.is_exact_out().then_some(self.other_amount_threshold)
+    }
+
+    /// Estimates the quote for `new_amount` of the fixed side of the swap (`in_amount` for
+    /// `ExactIn`, `out_amount` for `ExactOut`) by scaling the amounts, threshold, platform fee
+    /// and USD values linearly, without fetching a new quote. This ignores non-linear price
+    /// impact, so the estimate worsens as `new_amount` moves away from the quoted amount. The
+    /// route plan is left as quoted and `is_estimate` is set.
+    pub fn estimate_for_amount(&self, new_amount: u64) -> Quote {
+        let quoted_amount = if self.is_exact_out() {
+            self.out_amount
+        } else {
+            self.in_amount
+        };
+        let scale = |amount: u64| {
+            if quoted_amount == 0 {
+                0
+            } else {
+                (amount as u128 * new_amount as u128 / quoted_amount as u128)
+                    .try_into()
+                    .unwrap_or(u64::MAX)
+            }
+        };
+        let ratio = if quoted_amount == 0 {
+            0.
+        } else {
+            new_amount as f64 / quoted_amount as f64
+        };
+
+        let mut quote = self.clone();
+        if self.is_exact_out() {
+            quote.in_amount = scale(self.in_amount);
+            quote.out_amount = new_amount;
+        } else {
+            quote.in_amount = new_amount;
+            quote.out_amount = scale(self.out_amount);
+        }
+        quote.other_amount_threshold = scale(self.other_amount_threshold);
+        if let Some(platform_fee) = &mut quote.platform_fee {
+            platform_fee.amount = scale(platform_fee.amount);
+        }
+        for usd_value in [
+            &mut quote.in_usd_value,
+            &mut quote.out_usd_value,
+            &mut quote.swap_usd_value,
+        ] {
+            *usd_value = usd_value.map(|usd_value| usd_value * ratio);
+        }
+        quote.is_estimate = true;
+        quote
+    }
+
+    /// Number of hops in the route. The legs of a split share an input mint and count as one
+    /// hop.
+    pub fn hop_count(&self) -> usize {
+        self.route_plan
+            .iter()
+            .map(|route_plan| route_plan.swap_info.input_mint)
+            .unique()
+            .count()
+    }
+
+    /// Whether the route swaps straight from the input to the output mint, possibly split
+    /// across several DEXes
+    pub fn is_direct(&self) -> bool {
+        self.hop_count() == 1
+    }
+
+    /// Whether some leg of the route swaps on the DEX with the given label
+    pub fn contains_dex(&self, label: &str) -> bool {
+        self.route_plan
+            .iter()
+            .any(|route_plan| route_plan.swap_info.label.as_deref() == Some(label))
+    }
+
+    /// Whether every leg of the route swaps on one of the DEXes with the given labels. A leg
+    /// without a label never matches.
+    pub fn uses_only_dexes(&self, labels: &[&str]) -> bool {
+        self.route_plan.iter().all(|route_plan| {
+            route_plan
+                .swap_info
+                .label
+                .as_deref()
+                .is_some_and(|label| labels.contains(&label))
+        })
+    }
+
+    /// Whether some hop of the route swaps into `mint` on the way to the output mint
+    pub fn routes_through(&self, mint: &Pubkey) -> bool {
+        *mint != self.output_mint
+            && self
+                .route_plan
+                .iter()
+                .any(|route_plan| route_plan.swap_info.output_mint == *mint)
+    }
+
+    /// Renders every hop of the route plan on its own line, for debugging and logging
+    pub fn render_route(&self) -> String {
+        let mut route = format!(
+            "{} {} -> {} {} ({})",
+            self.in_amount,
+            short_pubkey(&self.input_mint),
+            self.out_amount,
+            short_pubkey(&self.output_mint),
+            self.swap_mode,
+        );
+        for (i, route_plan) in self.route_plan.iter().enumerate() {
+            let swap_info = &route_plan.swap_info;
+            route.push_str(&format!(
+                "\n  {}. {:>3}% {}: {} {} -> {} {}, fee {} {}",
+                i + 1,
+                route_plan.percent,
+                swap_info.label.as_deref().unwrap_or("Unknown DEX"),
+                swap_info.in_amount,
+                short_pubkey(&swap_info.input_mint),
+                swap_info.out_amount,
+                short_pubkey(&swap_info.output_mint),
+                swap_info.fee_amount,
+                short_pubkey(&swap_info.fee_mint),
+            ));
+        }
+        route
+    }
+
+    /// Like the `Display` output, but with amounts converted to UI amounts
+    pub fn summary_with_decimals(&self, input_decimals: u8, output_decimals: u8) -> String {
+        format!(
+            "{} {} for {} {} via {} (price impact: {})",
+            ui_amount(self.in_amount, input_decimals),
+            self.input_mint,
+            ui_amount(self.out_amount, output_decimals),
+            self.output_mint,
+            self.first_label(),
+            self.price_impact_pct,
+        )
+    }
+}
+
+/// The headline figures of a quote without its route plan, for storing quotes compactly
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteSummary {
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub price_impact_pct: f64,
+    pub slippage_bps: u64,
+}
+
+impl From<&Quote> for QuoteSummary {
+    fn from(quote: &Quote) -> Self {
+        Self {
+            input_mint: quote.input_mint,
+            output_mint: quote.output_mint,
+            in_amount: quote.in_amount,
+            out_amount: quote.out_amount,
+            price_impact_pct: quote.price_impact_pct,
+            slippage_bps: quote.slippage_bps,
+        }
+    }
+}
+
+/// A non-fatal issue with a quote, see `Quote::warnings()`
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuoteWarning {
+    /// Price impact exceeds the threshold passed to `Quote::warnings()`
+    HighPriceImpact { price_impact_pct: f64 },
+    /// The route plan legs out of `input_mint` don't add up to 100%, usually due to rounding
+    RoutePercentMismatch {
+        input_mint: Pubkey,
+        percent_sum: u32,
+    },
+}
+
+impl fmt::Display for Quote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} for {} {} via {} (price impact: {})",
+            self.in_amount,
+            self.input_mint,
+            self.out_amount,
+            self.output_mint,
+            self.first_label(),
+            self.price_impact_pct,
+        )
+    }
+}
+
+impl<'a> IntoIterator for &'a Quote {
+    type Item = &'a RoutePlan;
+    type IntoIter = std::slice::Iter<'a, RoutePlan>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.route_plan.iter()
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "1 {} ({}) = {} {} ({})",
+            self.input_symbol, self.input_mint, self.price, self.output_symbol, self.output_mint,
+        )
+    }
+}
+
+fn short_pubkey(pubkey: &Pubkey) -> String {
+    let pubkey = pubkey.to_string();
+    format!("{}..{}", &pubkey[..4], &pubkey[pubkey.len() - 4..])
+}
+
+fn ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10_f64.powi(decimals as i32)
+}
+
+fn check_ui_amount(ui_amount: f64) -> Result<()> {
+    if !(ui_amount.is_finite() && ui_amount > 0.) {
+        return Err(Error::InvalidConfig(format!(
+            "ui_amount must be a positive number, got {ui_amount}"
+        )));
+    }
+    Ok(())
+}
+
+fn ui_amount_to_amount(ui_amount: f64, decimals: u8) -> Result<u64> {
+    check_ui_amount(ui_amount)?;
+    let amount = (ui_amount * 10_f64.powi(decimals as i32)).round();
+    // `u64::MAX as f64` rounds up to 2^64, the first value out of range
+    if amount >= u64::MAX as f64 {
+        return Err(Error::InvalidConfig(format!(
+            "ui_amount {ui_amount} with {decimals} decimals exceeds u64::MAX base units"
+        )));
+    }
+    Ok(amount as u64)
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlatformFee {
     #[serde(with = "field_as_string")]
     pub amount: u64,
+    #[serde(with = "field_number_or_string")]
     pub fee_bps: u64,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl PlatformFee {
+    /// `amount` in whole tokens of the fee mint with the given decimals
+    pub fn ui_amount(&self, decimals: u8) -> f64 {
+        ui_amount(self.amount, decimals)
+    }
+
+    /// `fee_bps` as a percentage
+    pub fn percent(&self) -> f64 {
+        self.fee_bps as f64 / 100.
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RoutePlan {
     pub swap_info: SwapInfo,
     pub percent: u8,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl RoutePlan {
+    /// `percent` as a fraction between 0 and 1
+    pub fn fraction(&self) -> f64 {
+        self.percent as f64 / 100.
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SwapInfo {
     #[serde(with = "field_as_string")]
@@ -142,8 +648,402 @@ pub struct Swap {
     pub last_valid_block_height: u64,
 }
 
+impl Swap {
+    /// Number of signatures the transaction requires
+    pub fn required_signature_count(&self) -> usize {
+        self.swap_transaction
+            .message
+            .header()
+            .num_required_signatures as usize
+    }
+
+    /// Number of signatures present in the transaction
+    pub fn signature_count(&self) -> usize {
+        self.swap_transaction
+            .signatures
+            .iter()
+            .filter(|signature| **signature != Signature::default())
+            .count()
+    }
+
+    /// Static account keys the swap transaction marks writable, per its message header.
+    /// Writable accounts loaded from address lookup tables are not included.
+    pub fn writable_accounts(&self) -> Vec<Pubkey> {
+        let message = &self.swap_transaction.message;
+        let header = message.header();
+        let account_keys = message.static_account_keys();
+        let num_required_signatures = header.num_required_signatures as usize;
+        let num_writable_signed =
+            num_required_signatures.saturating_sub(header.num_readonly_signed_accounts as usize);
+        let num_writable_keys = account_keys
+            .len()
+            .saturating_sub(header.num_readonly_unsigned_accounts as usize);
+
+        account_keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                *index < num_writable_signed
+                    || (num_required_signatures..num_writable_keys).contains(index)
+            })
+            .map(|(_, key)| *key)
+            .collect()
+    }
+
+    /// Size of the serialized transaction in bytes, to compare against
+    /// `solana_sdk::packet::PACKET_DATA_SIZE`
+    pub fn serialized_size(&self) -> Result<usize> {
+        Ok(bincode::serialized_size(&self.swap_transaction)? as usize)
+    }
+
+    /// Upper bound of `serialized_size()` once `extra_instructions` are added to the swap
+    /// transaction. Accounts not among the static account keys are counted as new static keys,
+    /// even if an address lookup table could load them, and signers not among the required
+    /// signers as new signatures
+    pub fn serialized_size_with(&self, extra_instructions: &[Instruction]) -> Result<usize> {
+        let message = &self.swap_transaction.message;
+        let static_account_keys = message.static_account_keys();
+        let mut account_keys = static_account_keys.iter().collect::<HashSet<_>>();
+        let account_keys_len = account_keys.len();
+        let mut signers = static_account_keys
+            .iter()
+            .take(message.header().num_required_signatures as usize)
+            .collect::<HashSet<_>>();
+        let signers_len = signers.len();
+        let signatures_len = self.swap_transaction.signatures.len();
+        let instructions_len = message.instructions().len();
+
+        let mut size = self.serialized_size()?;
+        for instruction in extra_instructions {
+            for key in std::iter::once(&instruction.program_id)
+                .chain(instruction.accounts.iter().map(|account| &account.pubkey))
+            {
+                if account_keys.insert(key) {
+                    size += std::mem::size_of::<Pubkey>();
+                }
+            }
+            for account in instruction
+                .accounts
+                .iter()
+                .filter(|account| account.is_signer)
+            {
+                if signers.insert(&account.pubkey) {
+                    size += std::mem::size_of::<Signature>();
+                }
+            }
+            size += 1
+                + short_vec_len(instruction.accounts.len())
+                + instruction.accounts.len()
+                + short_vec_len(instruction.data.len())
+                + instruction.data.len();
+        }
+        size += short_vec_len(account_keys.len()) - short_vec_len(account_keys_len);
+        size += short_vec_len(signatures_len + signers.len() - signers_len)
+            - short_vec_len(signatures_len);
+        size += short_vec_len(instructions_len + extra_instructions.len())
+            - short_vec_len(instructions_len);
+        Ok(size)
+    }
+
+    /// Sets the compute unit price of the swap transaction, rewriting its `SetComputeUnitPrice`
+    /// instruction or inserting one if there is none. Existing signatures become invalid, so the
+    /// transaction must be signed afterwards.
+    pub fn set_compute_unit_price(&mut self, micro_lamports: u64) -> Result<()> {
+        let data = std::iter::once(SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR)
+            .chain(micro_lamports.to_le_bytes())
+            .collect::<Vec<_>>();
+        let (header, account_keys, instructions) = match &mut self.swap_transaction.message {
+            VersionedMessage::Legacy(message) => (
+                &mut message.header,
+                &mut message.account_keys,
+                &mut message.instructions,
+            ),
+            VersionedMessage::V0(message) => (
+                &mut message.header,
+                &mut message.account_keys,
+                &mut message.instructions,
+            ),
+        };
+
+        let program_id_index = match account_keys
+            .iter()
+            .position(|key| *key == COMPUTE_BUDGET_PROGRAM_ID)
+        {
+            Some(program_id_index) => program_id_index as u8,
+            None => {
+                // The program id is appended as the last static key, which shifts the indices of
+                // accounts loaded from address lookup tables by one
+                let program_id_index = account_keys.len() as u8;
+                for instruction in instructions.iter_mut() {
+                    for account_index in instruction.accounts.iter_mut() {
+                        if *account_index >= program_id_index {
+                            *account_index = account_index.checked_add(1).ok_or_else(|| {
+                                Error::InvalidTransaction(
+                                    "no room for the compute budget program account".into(),
+                                )
+                            })?;
+                        }
+                    }
+                }
+                account_keys.push(COMPUTE_BUDGET_PROGRAM_ID);
+                header.num_readonly_unsigned_accounts += 1;
+                program_id_index
+            }
+        };
+
+        match instructions.iter_mut().find(|instruction| {
+            instruction.program_id_index == program_id_index
+                && instruction.data.first() == Some(&SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR)
+        }) {
+            Some(instruction) => instruction.data = data,
+            None => instructions.insert(
+                0,
+                CompiledInstruction {
+                    program_id_index,
+                    accounts: vec![],
+                    data,
+                },
+            ),
+        }
+        Ok(())
+    }
+
+    /// Expected fee of the swap transaction in lamports: the base fee of each required
+    /// signature plus the prioritization fee set by its compute budget instructions. A Jito tip
+    /// is a transfer rather than a fee, so it is not included.
+    pub fn estimated_fee_lamports(&self) -> u64 {
+        let message = &self.swap_transaction.message;
+        let account_keys = message.static_account_keys();
+
+        let mut compute_unit_limit = None;
+        let mut compute_unit_price = 0;
+        let mut instruction_count = 0;
+        for instruction in message.instructions() {
+            if account_keys.get(instruction.program_id_index as usize)
+                != Some(&COMPUTE_BUDGET_PROGRAM_ID)
+            {
+                instruction_count += 1;
+                continue;
+            }
+            match instruction.data.split_first() {
+                Some((&SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR, units)) => {
+                    if let Ok(units) = units.try_into() {
+                        compute_unit_limit = Some(u32::from_le_bytes(units) as u64);
+                    }
+                }
+                Some((&SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR, micro_lamports)) => {
+                    if let Ok(micro_lamports) = micro_lamports.try_into() {
+                        compute_unit_price = u64::from_le_bytes(micro_lamports);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let compute_unit_limit = compute_unit_limit
+            .unwrap_or(instruction_count * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+            .min(MAX_COMPUTE_UNIT_LIMIT);
+
+        let priority_fee = (compute_unit_limit as u128 * compute_unit_price as u128)
+            .div_ceil(1_000_000)
+            .try_into()
+            .unwrap_or(u64::MAX);
+        (self.required_signature_count() as u64 * LAMPORTS_PER_SIGNATURE)
+            .saturating_add(priority_fee)
+    }
+
+    /// Signs the swap transaction in place with whichever of its required signers are given,
+    /// leaving the other signatures as they are. Changing `recent_blockhash` clears all existing
+    /// signatures, so each party must sign against the same blockhash.
+    pub fn partial_sign(&mut self, signers: &[&dyn Signer], recent_blockhash: Hash) -> Result<()> {
+        let transaction = &mut self.swap_transaction;
+        let required_signature_count =
+            transaction.message.header().num_required_signatures as usize;
+        if *transaction.message.recent_blockhash() != recent_blockhash {
+            transaction.message.set_recent_blockhash(recent_blockhash);
+            transaction.signatures.clear();
+        }
+        transaction
+            .signatures
+            .resize(required_signature_count, Signature::default());
+
+        let message_data = transaction.message.serialize();
+        for signer in signers {
+            let pubkey = signer.try_pubkey()?;
+            let index = transaction
+                .message
+                .static_account_keys()
+                .iter()
+                .take(required_signature_count)
+                .position(|key| *key == pubkey)
+                .ok_or_else(|| {
+                    Error::InvalidTransaction(format!("{pubkey} is not a required signer"))
+                })?;
+            transaction.signatures[index] = signer.try_sign_message(&message_data)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the Jupiter route instruction of the swap transaction swaps between the mints of
+    /// `quote`, for its amounts, slippage and platform fee. Mints are read from the instruction's
+    /// source and destination mint accounts, so a swap does not match if they are loaded from an
+    /// address lookup table, or if it uses a `route` instruction, which has no source mint
+    /// account. A swap requested with `SwapRequest::dynamic_slippage` carries the slippage
+    /// Jupiter picked, so check it with `matches_request()` instead.
+    pub fn matches_quote(&self, quote: &Quote) -> bool {
+        self.matches(quote, |slippage_bps| slippage_bps == quote.slippage_bps)
+    }
+
+    /// Like `matches_quote()` for the quote of `swap_request`, but with dynamic slippage
+    /// accepts any slippage within the requested bounds
+    pub fn matches_request(&self, swap_request: &SwapRequest) -> bool {
+        let quote = &swap_request.quote_response;
+        match &swap_request.dynamic_slippage {
+            Some(dynamic_slippage) => self.matches(quote, |slippage_bps| {
+                (dynamic_slippage.min_bps..=dynamic_slippage.max_bps).contains(&slippage_bps)
+            }),
+            None => self.matches_quote(quote),
+        }
+    }
+
+    fn matches(&self, quote: &Quote, slippage_matches: impl Fn(u64) -> bool) -> bool {
+        let message = &self.swap_transaction.message;
+        let account_keys = message.static_account_keys();
+        let Some(instruction) = message.instructions().iter().find(|instruction| {
+            account_keys.get(instruction.program_id_index as usize) == Some(&JUPITER_PROGRAM_ID)
+        }) else {
+            return false;
+        };
+        let Some(layout) = instruction.data.get(..8).and_then(|discriminator| {
+            RouteLayout::from_discriminator(discriminator.try_into().ok()?)
+        }) else {
+            return false;
+        };
+        if layout.exact_out != quote.is_exact_out() {
+            return false;
+        }
+
+        let mint = |position: Option<usize>| {
+            position
+                .and_then(|position| instruction.accounts.get(position))
+                .and_then(|index| account_keys.get(*index as usize))
+        };
+        if mint(layout.source_mint) != Some(&quote.input_mint)
+            || mint(Some(layout.destination_mint)) != Some(&quote.output_mint)
+        {
+            return false;
+        }
+
+        // Every route instruction ends with the fixed amount (except with a token ledger, which
+        // supplies it), the quoted amount, the slippage and the platform fee
+        let tail_len = if layout.token_ledger { 11 } else { 19 };
+        let Some(tail) = instruction
+            .data
+            .len()
+            .checked_sub(tail_len)
+            .filter(|tail_start| *tail_start >= 8)
+            .map(|tail_start| &instruction.data[tail_start..])
+        else {
+            return false;
+        };
+        let (amount, tail) = if layout.token_ledger {
+            (None, tail)
+        } else {
+            let (amount, tail) = tail.split_at(8);
+            (Some(u64::from_le_bytes(amount.try_into().unwrap())), tail)
+        };
+        let quoted_amount = u64::from_le_bytes(tail[0..8].try_into().unwrap());
+        let slippage_bps = u16::from_le_bytes(tail[8..10].try_into().unwrap());
+        let platform_fee_bps = tail[10];
+
+        let (expected_amount, expected_quoted_amount) = if quote.is_exact_out() {
+            (quote.out_amount, quote.in_amount)
+        } else {
+            (quote.in_amount, quote.out_amount)
+        };
+        amount.is_none_or(|amount| amount == expected_amount)
+            && quoted_amount == expected_quoted_amount
+            && slippage_matches(slippage_bps as u64)
+            && platform_fee_bps as u64
+                == quote
+                    .platform_fee
+                    .as_ref()
+                    .map_or(0, |platform_fee| platform_fee.fee_bps)
+    }
+
+    /// Whether every required signature is present
+    pub fn is_fully_signed(&self) -> bool {
+        let required_signature_count = self.required_signature_count();
+        self.swap_transaction.signatures.len() >= required_signature_count
+            && self.swap_transaction.signatures[..required_signature_count]
+                .iter()
+                .all(|signature| *signature != Signature::default())
+    }
+}
+
+const JUPITER_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Where a Jupiter route instruction keeps its mint accounts and amounts
+struct RouteLayout {
+    /// Position of the source mint among the instruction's accounts, if it has one
+    source_mint: Option<usize>,
+    destination_mint: usize,
+    /// Whether the input amount is read from a token ledger rather than the instruction data
+    token_ledger: bool,
+    exact_out: bool,
+}
+
+impl RouteLayout {
+    fn from_discriminator(discriminator: [u8; 8]) -> Option<Self> {
+        let (source_mint, destination_mint, token_ledger, exact_out) = match discriminator {
+            // route
+            [229, 23, 203, 151, 122, 227, 173, 42] => (None, 5, false, false),
+            // route_with_token_ledger
+            [150, 86, 71, 116, 167, 93, 14, 104] => (None, 5, true, false),
+            // exact_out_route
+            [208, 51, 239, 151, 123, 43, 237, 92] => (Some(5), 6, false, true),
+            // shared_accounts_route
+            [193, 32, 155, 51, 65, 214, 156, 129] => (Some(7), 8, false, false),
+            // shared_accounts_route_with_token_ledger
+            [230, 121, 143, 80, 119, 159, 106, 170] => (Some(7), 8, true, false),
+            // shared_accounts_exact_out_route
+            [176, 209, 105, 168, 154, 125, 69, 62] => (Some(7), 8, false, true),
+            _ => return None,
+        };
+        Some(Self {
+            source_mint,
+            destination_mint,
+            token_ledger,
+            exact_out,
+        })
+    }
+}
+
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+
+const SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR: u8 = 3;
+
+/// Compute units each instruction may use when no `SetComputeUnitLimit` is given
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Largest serialized transaction that fits in a packet
+const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Length of the compact-u16 prefix of a serialized vector of `len` items
+fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
 /// Swap instructions
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SwapInstructions {
     #[serde(with = "field_instruction::option_instruction")]
@@ -159,91 +1059,661 @@ pub struct SwapInstructions {
     #[serde(with = "field_pubkey::vec")]
     pub address_lookup_table_addresses: Vec<Pubkey>,
     pub prioritization_fee_lamports: u64,
+    /// How `prioritization_fee_lamports` is paid, when the API reports it
+    #[serde(default)]
+    pub prioritization_type: Option<PrioritizationType>,
+}
+
+/// How the prioritization fee of a swap is paid
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PrioritizationType {
+    /// Compute unit price set through a compute budget instruction
+    #[serde(rename_all = "camelCase")]
+    ComputeBudget {
+        micro_lamports: u64,
+        estimated_micro_lamports: Option<u64>,
+    },
+    /// Tip paid to a Jito validator
+    Jito { lamports: u64 },
+    /// A prioritization type this crate does not know yet, as sent by the API
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
 }
 
 /// Hashmap of possible swap routes from input mint to an array of output mints
 pub type RouteMap = HashMap<Pubkey, Vec<Pubkey>>;
 
-fn maybe_jupiter_api_error<T>(value: serde_json::Value) -> Result<T>
-where
-    T: serde::de::DeserializeOwned,
-{
-    #[derive(Deserialize)]
-    struct ErrorResponse {
-        error: String,
+/// Hashmap of DEX program id to DEX label
+pub type ProgramIdToLabel = HashMap<Pubkey, String>;
+
+/// Token metadata from the Jupiter token list
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Token {
+    #[serde(with = "field_as_string", alias = "mint")]
+    pub address: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(rename = "logoURI", alias = "logo_uri")]
+    pub logo_uri: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Unix timestamp of when Jupiter first saw the mint, when the API includes it
+    #[serde(default, with = "field_number_or_string::option")]
+    pub created_at: Option<u64>,
+    /// Authority that can freeze token accounts of the mint, when the API includes it
+    #[serde(default, alias = "freezeAuthority", with = "field_pubkey::option")]
+    pub freeze_authority: Option<Pubkey>,
+    /// Token program owning the mint, Token or Token-2022, when the API includes it
+    #[serde(default, alias = "tokenProgram", with = "field_pubkey::option")]
+    pub token_program: Option<Pubkey>,
+}
+
+impl Token {
+    /// Whether the token is on Jupiter's verified list
+    pub fn is_verified(&self) -> bool {
+        self.tags.iter().any(|tag| tag == "verified")
     }
-    if let Ok(ErrorResponse { error }) = serde_json::from_value::<ErrorResponse>(value.clone()) {
-        Err(Error::JupiterApi(error))
-    } else {
-        serde_json::from_value(value).map_err(|err| err.into())
+}
+
+/// Token list indexed by mint and symbol
+#[derive(Clone, Debug, Default)]
+pub struct TokenList {
+    tokens: Vec<Token>,
+    by_mint: HashMap<Pubkey, usize>,
+    by_symbol: HashMap<String, Vec<usize>>,
+}
+
+impl From<Vec<Token>> for TokenList {
+    fn from(tokens: Vec<Token>) -> Self {
+        let mut by_mint = HashMap::with_capacity(tokens.len());
+        let mut by_symbol = HashMap::<String, Vec<usize>>::new();
+        for (i, token) in tokens.iter().enumerate() {
+            by_mint.insert(token.address, i);
+            by_symbol
+                .entry(token.symbol.to_uppercase())
+                .or_default()
+                .push(i);
+        }
+        Self {
+            tokens,
+            by_mint,
+            by_symbol,
+        }
     }
 }
 
-/// Get simple price for a given input mint, output mint, and amount
-pub async fn price(input_mint: Pubkey, output_mint: Pubkey, ui_amount: f64) -> Result<Price> {
-    let url = format!(
-        "{base_url}/price?id={input_mint}&vsToken={output_mint}&amount={ui_amount}",
-        base_url = price_api_url(),
-    );
-    maybe_jupiter_api_error(reqwest::get(url).await?.json().await?)
+impl TokenList {
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn by_mint(&self, mint: &Pubkey) -> Option<&Token> {
+        self.by_mint.get(mint).map(|i| &self.tokens[*i])
+    }
+
+    /// All tokens with the given symbol, compared case-insensitively
+    pub fn all_by_symbol(&self, symbol: &str) -> Vec<&Token> {
+        self.by_symbol
+            .get(&symbol.to_uppercase())
+            .map(|indices| indices.iter().map(|i| &self.tokens[*i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// The token with the given symbol, preferring a verified token when symbols collide
+    pub fn by_symbol(&self, symbol: &str) -> Option<&Token> {
+        let tokens = self.all_by_symbol(symbol);
+        tokens
+            .iter()
+            .find(|token| token.is_verified())
+            .or_else(|| tokens.first())
+            .copied()
+    }
+
+    /// The single verified token with the given symbol, or the only token with that symbol if
+    /// none are verified
+    pub fn resolve_symbol(&self, symbol: &str) -> Result<&Token> {
+        let tokens = self.all_by_symbol(symbol);
+        let verified_tokens = tokens
+            .iter()
+            .copied()
+            .filter(|token| token.is_verified())
+            .collect::<Vec<_>>();
+        let candidates = if verified_tokens.is_empty() {
+            tokens
+        } else {
+            verified_tokens
+        };
+
+        match candidates[..] {
+            [] => Err(Error::UnknownToken(symbol.to_string())),
+            [token] => Ok(token),
+            _ => Err(Error::AmbiguousToken {
+                symbol: symbol.to_string(),
+                candidates: candidates.iter().map(|token| token.address).collect(),
+            }),
+        }
+    }
+
+    /// The decimals of `mint`, failing with `Error::UnknownMint` if it is not in the list
+    pub fn decimals(&self, mint: &Pubkey) -> Result<u8> {
+        self.by_mint(mint)
+            .map(|token| token.decimals)
+            .ok_or(Error::UnknownMint(*mint))
+    }
+
+    /// Converts `ui_amount` of `mint` to base units
+    ///
+    /// Fails with `Error::InvalidConfig` if `ui_amount` is not a positive number or its base units
+    /// exceed `u64::MAX`
+    pub fn to_raw_amount(&self, mint: &Pubkey, ui_amount: f64) -> Result<u64> {
+        ui_amount_to_amount(ui_amount, self.decimals(mint)?)
+    }
+
+    /// Converts `amount` base units of `mint` to whole tokens
+    pub fn to_ui_amount(&self, mint: &Pubkey, amount: u64) -> Result<f64> {
+        Ok(ui_amount(amount, self.decimals(mint)?))
+    }
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
-pub enum SwapMode {
-    #[default]
-    ExactIn,
-    ExactOut,
+impl Error {
+    /// The `errorCode` of a failed Jupiter API response, if it had one
+    pub fn jupiter_error_code(&self) -> Option<&JupiterErrorCode> {
+        match self {
+            Self::JupiterApi { code, .. } => code.as_ref(),
+            _ => None,
+        }
+    }
 }
 
-impl FromStr for SwapMode {
-    type Err = Error;
+/// The `errorCode` of a failed Jupiter API response
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+pub enum JupiterErrorCode {
+    CouldNotFindAnyRoute,
+    NoRoutesFound,
+    TokenNotTradable,
+    CircularArbitrageIsDisabled,
+    RoutePlanDoesNotConsumeAllTheAmount,
+    InvalidComputeUnitPrice,
+    /// A code not known to this crate
+    Other(String),
+}
 
-    fn from_str(s: &str) -> Result<Self> {
-        match s {
-            "ExactIn" => Ok(Self::ExactIn),
-            "ExactOut" => Ok(Self::ExactOut),
-            _ => Err(Error::ParseSwapMode { value: s.into() }),
+impl From<String> for JupiterErrorCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "COULD_NOT_FIND_ANY_ROUTE" => Self::CouldNotFindAnyRoute,
+            "NO_ROUTES_FOUND" => Self::NoRoutesFound,
+            "TOKEN_NOT_TRADABLE" => Self::TokenNotTradable,
+            "CIRCULAR_ARBITRAGE_IS_DISABLED" => Self::CircularArbitrageIsDisabled,
+            "ROUTE_PLAN_DOES_NOT_CONSUME_ALL_THE_AMOUNT" => {
+                Self::RoutePlanDoesNotConsumeAllTheAmount
+            }
+            "INVALID_COMPUTE_UNIT_PRICE" => Self::InvalidComputeUnitPrice,
+            _ => Self::Other(code),
         }
     }
 }
 
-impl fmt::Display for SwapMode {
+impl fmt::Display for JupiterErrorCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Self::ExactIn => write!(f, "ExactIn"),
-            Self::ExactOut => write!(f, "ExactOut"),
+        match self {
+            Self::CouldNotFindAnyRoute => write!(f, "COULD_NOT_FIND_ANY_ROUTE"),
+            Self::NoRoutesFound => write!(f, "NO_ROUTES_FOUND"),
+            Self::TokenNotTradable => write!(f, "TOKEN_NOT_TRADABLE"),
+            Self::CircularArbitrageIsDisabled => write!(f, "CIRCULAR_ARBITRAGE_IS_DISABLED"),
+            Self::RoutePlanDoesNotConsumeAllTheAmount => {
+                write!(f, "ROUTE_PLAN_DOES_NOT_CONSUME_ALL_THE_AMOUNT")
+            }
+            Self::InvalidComputeUnitPrice => write!(f, "INVALID_COMPUTE_UNIT_PRICE"),
+            Self::Other(code) => write!(f, "{code}"),
         }
     }
 }
 
-#[derive(Default)]
-pub struct QuoteConfig {
-    pub slippage_bps: Option<u64>,
-    pub swap_mode: Option<SwapMode>,
-    pub dexes: Option<Vec<String>>,
-    pub exclude_dexes: Option<Vec<String>>,
-    pub only_direct_routes: bool,
-    pub as_legacy_transaction: Option<bool>,
-    pub platform_fee_bps: Option<u64>,
-    pub max_accounts: Option<u64>,
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    error: String,
+    error_code: Option<JupiterErrorCode>,
 }
 
-/// Get quote for a given input mint, output mint, and amount
-pub async fn quote(
+fn maybe_jupiter_api_error<T>(body: &str) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    if let Ok(ErrorResponse { error, error_code }) = serde_json::from_str::<ErrorResponse>(body) {
+        Err(Error::JupiterApi {
+            status: None,
+            message: error,
+            code: error_code,
+        })
+    } else {
+        serde_json::from_str(body).map_err(|err| err.into())
+    }
+}
+
+static MAX_CONCURRENT_REQUESTS: std::sync::OnceLock<tokio::sync::Semaphore> =
+    std::sync::OnceLock::new();
+
+/// Caps the number of Jupiter API requests in flight across the whole process at `n`, to stay
+/// under rate limits however many calls run concurrently. Can only be set once.
+pub fn set_max_concurrent_requests(n: usize) -> Result<()> {
+    if n == 0 {
+        return Err(Error::InvalidConfig(
+            "max concurrent requests must be positive".into(),
+        ));
+    }
+    MAX_CONCURRENT_REQUESTS
+        .set(tokio::sync::Semaphore::new(n))
+        .map_err(|_| Error::InvalidConfig("max concurrent requests is already set".into()))
+}
+
+static HTTP_CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+
+/// Sends every request through `client` rather than a default `reqwest::Client`, to tune its
+/// connection pool, timeouts or HTTP version, e.g. with `http2_prior_knowledge()` for an
+/// HTTP/2-only endpoint. Must be called before the first request, as that installs the default
+/// client. Can only be set once.
+pub fn set_http_client(client: reqwest::Client) -> Result<()> {
+    HTTP_CLIENT
+        .set(client)
+        .map_err(|_| Error::InvalidConfig("HTTP client is already set".into()))
+}
+
+/// The client installed with `set_http_client()`, shared so its connections are reused
+fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Until when every request waits after a 429 response with a `Retry-After` header
+static COOL_DOWN_UNTIL: std::sync::Mutex<Option<tokio::time::Instant>> =
+    std::sync::Mutex::new(None);
+
+/// Sends `request` and deserializes its response, see `send_for_body()`
+async fn send<T>(request: reqwest::RequestBuilder) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    maybe_jupiter_api_error(&send_for_body(request).await?)
+}
+
+/// Like `send()`, but deserializes on a blocking thread, for multi-megabyte responses that
+/// would otherwise stall the runtime
+async fn send_with_blocking_parse<T>(request: reqwest::RequestBuilder) -> Result<T>
+where
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    let body = send_for_body(request).await?;
+    tokio::task::spawn_blocking(move || maybe_jupiter_api_error(&body)).await?
+}
+
+/// Sends `request` once any `Retry-After` cool-down has elapsed and a slot under
+/// `set_max_concurrent_requests()` is free, holding the slot until the response is read
+async fn send_for_body(request: reqwest::RequestBuilder) -> Result<String> {
+    let cool_down_until = *COOL_DOWN_UNTIL
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    if let Some(cool_down_until) = cool_down_until {
+        tokio::time::sleep_until(cool_down_until).await;
+    }
+
+    let _permit = match MAX_CONCURRENT_REQUESTS.get() {
+        Some(semaphore) => semaphore.acquire().await.ok(),
+        None => None,
+    };
+    let response = request.send().await?;
+
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        if let Some(retry_after) = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|retry_after| retry_after.to_str().ok())
+            .and_then(|retry_after| retry_after.parse::<u64>().ok())
+        {
+            let until = tokio::time::Instant::now() + Duration::from_secs(retry_after);
+            let mut cool_down_until = COOL_DOWN_UNTIL
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            *cool_down_until = Some(cool_down_until.map_or(until, |current| current.max(until)));
+        }
+    }
+    response_body(response).await
+}
+
+/// Returns the body of a successful response, or `Error::JupiterApi` with the `{error}`
+/// message and `errorCode` of a failed one, falling back to its raw body
+async fn response_body(response: reqwest::Response) -> Result<String> {
+    let status = response.status();
+    let endpoint = response.url().path().to_string();
+    let body = response.text().await?;
+    if status.is_success() {
+        if body.trim().is_empty() {
+            return Err(Error::EmptyResponse { endpoint });
+        }
+        return Ok(body);
+    }
+
+    let (message, code) = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(ErrorResponse { error, error_code }) => (error, error_code),
+        Err(_) => (body, None),
+    };
+    Err(Error::JupiterApi {
+        status: Some(status),
+        message,
+        code,
+    })
+}
+
+/// Runs `future` until it completes or `deadline` passes, in which case
+/// `Error::DeadlineExceeded` is returned. Passing the same deadline to each step of a
+/// quote-swap-send sequence bounds the latency of the whole sequence.
+pub async fn with_deadline<T>(
+    deadline: std::time::Instant,
+    future: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout_at(deadline.into(), future)
+        .await
+        .unwrap_or(Err(Error::DeadlineExceeded))
+}
+
+/// Round-trip time of a `HEAD` request to `base_url`, for picking the fastest of several
+/// endpoints. Any HTTP response counts, as only the latency is of interest.
+pub async fn measure_latency(base_url: &str) -> Result<Duration> {
+    let start = std::time::Instant::now();
+    http_client().head(base_url).send().await?;
+    Ok(start.elapsed())
+}
+
+/// Runs `future` until it completes or `cancellation_token` is cancelled, in which case
+/// `Error::Cancelled` is returned
+#[cfg(feature = "cancellation")]
+pub async fn cancellable<T>(
+    cancellation_token: &tokio_util::sync::CancellationToken,
+    future: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    cancellation_token
+        .run_until_cancelled(future)
+        .await
+        .unwrap_or(Err(Error::Cancelled))
+}
+
+/// Get simple price for a given input mint, output mint, and amount
+///
+/// `ui_amount` is in whole `input_mint` tokens, not base units. The returned `Price::price` is
+/// in whole `output_mint` tokens per whole `input_mint` token, whatever the decimals of either
+/// mint. Multiply by `10^decimals` of `output_mint` for its base units.
+pub async fn price(input_mint: Pubkey, output_mint: Pubkey, ui_amount: f64) -> Result<Price> {
+    price_with_base_url(&price_api_url(), input_mint, output_mint, ui_amount).await
+}
+
+/// Like `price()`, but against the price API at `base_url` instead of `PRICE_API_URL`
+pub async fn price_with_base_url(
+    base_url: &str,
     input_mint: Pubkey,
     output_mint: Pubkey,
+    ui_amount: f64,
+) -> Result<Price> {
+    check_ui_amount(ui_amount)?;
+
+    let url = format!("{base_url}/price?id={input_mint}&vsToken={output_mint}&amount={ui_amount}");
+    send(http_client().get(url)).await
+}
+
+/// Price with buy and sell side detail, see `price_with_extra_info()`. Prices are in whole
+/// `output_mint` tokens per whole `input_mint` token.
+#[derive(Clone, Debug)]
+pub struct PriceExtraInfo {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub price: f64,
+    /// Price of buying `input_mint`, from Jupiter's own quote
+    pub buy_price: Option<f64>,
+    /// Price of selling `input_mint`, from Jupiter's own quote
+    pub sell_price: Option<f64>,
+    /// `price` parsed exactly from the response, enabled by the `rust_decimal` feature
+    #[cfg(feature = "rust_decimal")]
+    pub price_decimal: rust_decimal::Decimal,
+    /// `buy_price` parsed exactly from the response, enabled by the `rust_decimal` feature
+    #[cfg(feature = "rust_decimal")]
+    pub buy_price_decimal: Option<rust_decimal::Decimal>,
+    /// `sell_price` parsed exactly from the response, enabled by the `rust_decimal` feature
+    #[cfg(feature = "rust_decimal")]
+    pub sell_price_decimal: Option<rust_decimal::Decimal>,
+    /// `low`, `medium` or `high`
+    pub confidence_level: Option<String>,
+    /// Price impact ratio of buying, keyed by trade size as reported by the API
+    pub buy_price_impact_ratio: HashMap<String, f64>,
+    /// Price impact ratio of selling, keyed by trade size as reported by the API
+    pub sell_price_impact_ratio: HashMap<String, f64>,
+}
+
+/// Get the price of `input_mint` in `output_mint` along with its buy and sell prices and depth,
+/// from the v2 price API at `PRICE_V2_API_URL`
+pub async fn price_with_extra_info(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+) -> Result<PriceExtraInfo> {
+    price_with_extra_info_with_base_url(&price_v2_api_url(), input_mint, output_mint).await
+}
+
+/// Like `price_with_extra_info()`, but against the v2 price API at `base_url` instead of
+/// `PRICE_V2_API_URL`
+pub async fn price_with_extra_info_with_base_url(
+    base_url: &str,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+) -> Result<PriceExtraInfo> {
+    #[derive(Deserialize)]
+    struct PriceResponse {
+        data: HashMap<String, Option<PriceData>>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PriceData {
+        #[cfg(not(feature = "rust_decimal"))]
+        #[serde(with = "field_number_or_string")]
+        price: f64,
+        #[cfg(feature = "rust_decimal")]
+        price: field_decimal::Exact,
+        extra_info: Option<ExtraInfo>,
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ExtraInfo {
+        quoted_price: Option<QuotedPrice>,
+        confidence_level: Option<String>,
+        depth: Option<Depth>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct QuotedPrice {
+        #[cfg(not(feature = "rust_decimal"))]
+        #[serde(default, with = "field_number_or_string::option")]
+        buy_price: Option<f64>,
+        #[cfg(feature = "rust_decimal")]
+        #[serde(default)]
+        buy_price: Option<field_decimal::Exact>,
+        #[cfg(not(feature = "rust_decimal"))]
+        #[serde(default, with = "field_number_or_string::option")]
+        sell_price: Option<f64>,
+        #[cfg(feature = "rust_decimal")]
+        #[serde(default)]
+        sell_price: Option<field_decimal::Exact>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Depth {
+        buy_price_impact_ratio: Option<PriceImpactRatio>,
+        sell_price_impact_ratio: Option<PriceImpactRatio>,
+    }
+
+    #[derive(Deserialize)]
+    struct PriceImpactRatio {
+        depth: HashMap<String, f64>,
+    }
+
+    let url = format!("{base_url}?ids={input_mint}&vsToken={output_mint}&showExtraInfo=true");
+    let PriceResponse { mut data } = send(http_client().get(url)).await?;
+    let PriceData { price, extra_info } = data
+        .remove(&input_mint.to_string())
+        .flatten()
+        .ok_or_else(|| Error::JupiterApi {
+            status: None,
+            message: format!("no price for {input_mint}"),
+            code: None,
+        })?;
+
+    let extra_info = extra_info.unwrap_or_default();
+    let (buy_price, sell_price) = extra_info
+        .quoted_price
+        .map(|quoted_price| (quoted_price.buy_price, quoted_price.sell_price))
+        .unwrap_or_default();
+    let (buy_price_impact_ratio, sell_price_impact_ratio) = extra_info
+        .depth
+        .map(|depth| (depth.buy_price_impact_ratio, depth.sell_price_impact_ratio))
+        .unwrap_or_default();
+    let depth =
+        |ratio: Option<PriceImpactRatio>| ratio.map(|ratio| ratio.depth).unwrap_or_default();
+
+    #[cfg(not(feature = "rust_decimal"))]
+    let price_extra_info = PriceExtraInfo {
+        input_mint,
+        output_mint,
+        price,
+        buy_price,
+        sell_price,
+        confidence_level: extra_info.confidence_level,
+        buy_price_impact_ratio: depth(buy_price_impact_ratio),
+        sell_price_impact_ratio: depth(sell_price_impact_ratio),
+    };
+    #[cfg(feature = "rust_decimal")]
+    let price_extra_info = PriceExtraInfo {
+        input_mint,
+        output_mint,
+        price: price.value,
+        buy_price: buy_price.as_ref().map(|buy_price| buy_price.value),
+        sell_price: sell_price.as_ref().map(|sell_price| sell_price.value),
+        price_decimal: price.decimal,
+        buy_price_decimal: buy_price.map(|buy_price| buy_price.decimal),
+        sell_price_decimal: sell_price.map(|sell_price| sell_price.decimal),
+        confidence_level: extra_info.confidence_level,
+        buy_price_impact_ratio: depth(buy_price_impact_ratio),
+        sell_price_impact_ratio: depth(sell_price_impact_ratio),
+    };
+    Ok(price_extra_info)
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub enum SwapMode {
+    #[default]
+    ExactIn,
+    ExactOut,
+}
+
+impl FromStr for SwapMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ExactIn" => Ok(Self::ExactIn),
+            "ExactOut" => Ok(Self::ExactOut),
+            _ => Err(Error::ParseSwapMode { value: s.into() }),
+        }
+    }
+}
+
+impl TryFrom<&str> for SwapMode {
+    type Error = Error;
+
+    /// Parses the swap mode case-insensitively, ignoring `_` and `-` separators, so that
+    /// `ExactIn`, `exactIn`, `exact_in` and `exact-in` are all accepted
+    fn try_from(s: &str) -> Result<Self> {
+        match s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-'))
+            .collect::<String>()
+            .to_lowercase()
+            .as_str()
+        {
+            "exactin" => Ok(Self::ExactIn),
+            "exactout" => Ok(Self::ExactOut),
+            _ => Err(Error::ParseSwapMode { value: s.into() }),
+        }
+    }
+}
+
+impl fmt::Display for SwapMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::ExactIn => write!(f, "ExactIn"),
+            Self::ExactOut => write!(f, "ExactOut"),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct QuoteConfig {
+    pub slippage_bps: Option<u64>,
+    pub swap_mode: Option<SwapMode>,
+    /// Only route through these DEX labels
+    pub dexes: Option<Vec<String>>,
+    pub exclude_dexes: Option<Vec<String>>,
+    /// Exclude the DEXes with these program ids. `quote()` resolves them to labels with
+    /// `cached_program_id_to_label()` and adds them to `exclude_dexes`.
+    pub exclude_program_ids: Option<Vec<Pubkey>>,
+    /// Check `dexes` and `exclude_dexes` against `supported_dexes()` before quoting, failing
+    /// with `Error::InvalidConfig` on an unknown label instead of having it silently ignored
+    pub validate_dexes: bool,
+    pub only_direct_routes: Option<bool>,
+    /// Quote a route that fits in a legacy transaction. The swap must then also set
+    /// `SwapRequest::as_legacy_transaction`, as the quote response does not record it.
+    pub as_legacy_transaction: Option<bool>,
+    /// Platform fee deducted in the quote's `platform_fee`. The quote API takes no referral
+    /// account; the fee is collected by the swap's `SwapRequest::fee_account`, see
+    /// `SwapRequest::with_referral()`.
+    pub platform_fee_bps: Option<u64>,
+    pub max_accounts: Option<u64>,
+    /// Only route through intermediate tokens with highly liquid markets. Together with `dexes`
+    /// every hop must satisfy both filters, which can leave no route for pairs that are only
+    /// reachable through an illiquid intermediate token on the listed DEXes.
+    pub restrict_intermediate_tokens: Option<bool>,
+    /// Prefer routing through the more liquid DEXes
+    pub prefer_liquid_dexes: Option<bool>,
+    /// Require the route to pass through at least one of these mints. The API cannot be asked for
+    /// this, so `quote()` checks the returned route and fails with
+    /// `Error::IntermediateTokenNotInRoute` otherwise.
+    pub intermediate_tokens: Option<Vec<Pubkey>>,
+    /// Additional query parameters appended to the quote URL, for API options not modeled here
+    pub extra_params: Vec<(String, String)>,
+}
+
+fn build_quote_url(
+    base_url: &str,
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
     amount: u64,
-    quote_config: QuoteConfig,
-) -> Result<Quote> {
+    quote_config: &QuoteConfig,
+) -> Result<String> {
     let url = format!(
-        "{base_url}/quote?inputMint={input_mint}&outputMint={output_mint}&amount={amount}&onlyDirectRoutes={}&{}{}{}{}{}{}{}",
-        quote_config.only_direct_routes,
+        "{base_url}/quote?inputMint={input_mint}&outputMint={output_mint}&amount={amount}{}{}{}{}{}{}{}{}{}{}",
+        quote_config
+            .only_direct_routes
+            .map(|only_direct_routes| format!("&onlyDirectRoutes={only_direct_routes}"))
+            .unwrap_or_default(),
         quote_config
             .as_legacy_transaction
             .map(|as_legacy_transaction| format!("&asLegacyTransaction={as_legacy_transaction}"))
             .unwrap_or_default(),
         quote_config
             .swap_mode
+            .as_ref()
             .map(|swap_mode| format!("&swapMode={swap_mode}"))
             .unwrap_or_default(),
         quote_config
@@ -252,30 +1722,449 @@ pub async fn quote(
             .unwrap_or_default(),
         quote_config
             .platform_fee_bps
-            .map(|platform_fee_bps| format!("&feeBps={platform_fee_bps}"))
+            .map(|platform_fee_bps| format!("&platformFeeBps={platform_fee_bps}"))
             .unwrap_or_default(),
         quote_config
             .dexes
-            .map(|dexes| format!("&dexes={}", dexes.into_iter().join(",")))
+            .as_ref()
+            .map(|dexes| format!("&dexes={}", dexes.iter().join(",")))
             .unwrap_or_default(),
         quote_config
             .exclude_dexes
-            .map(|exclude_dexes| format!("&excludeDexes={}", exclude_dexes.into_iter().join(",")))
+            .as_ref()
+            .map(|exclude_dexes| format!("&excludeDexes={}", exclude_dexes.iter().join(",")))
             .unwrap_or_default(),
         quote_config
             .max_accounts
             .map(|max_accounts| format!("&maxAccounts={max_accounts}"))
             .unwrap_or_default(),
-        base_url=quote_api_url(),
+        quote_config
+            .restrict_intermediate_tokens
+            .map(|restrict_intermediate_tokens| format!("&restrictIntermediateTokens={restrict_intermediate_tokens}"))
+            .unwrap_or_default(),
+        quote_config
+            .prefer_liquid_dexes
+            .map(|prefer_liquid_dexes| format!("&preferLiquidDexes={prefer_liquid_dexes}"))
+            .unwrap_or_default(),
     );
 
-    maybe_jupiter_api_error(reqwest::get(url).await?.json().await?)
+    if quote_config.extra_params.is_empty() {
+        return Ok(url);
+    }
+    let mut url = reqwest::Url::parse(&url)
+        .map_err(|err| Error::InvalidConfig(format!("invalid quote URL `{url}`: {err}")))?;
+    url.query_pairs_mut()
+        .extend_pairs(&quote_config.extra_params);
+    Ok(url.into())
+}
+
+/// Get quote for a given input mint, output mint, and amount
+pub async fn quote(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    quote_config: QuoteConfig,
+) -> Result<Quote> {
+    quote_with_base_url(
+        &quote_api_url(),
+        input_mint,
+        output_mint,
+        amount,
+        quote_config,
+    )
+    .await
+}
+
+/// Like `quote()`, but against the quote API at `base_url` instead of `QUOTE_API_URL`. DEX
+/// labels for `QuoteConfig::exclude_program_ids` and `validate_dexes` still come from
+/// `QUOTE_API_URL`.
+pub async fn quote_with_base_url(
+    base_url: &str,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    quote_config: QuoteConfig,
+) -> Result<Quote> {
+    quote_impl(
+        base_url,
+        input_mint,
+        output_mint,
+        amount,
+        quote_config,
+        false,
+    )
+    .await
+}
+
+/// Like `quote()`, but fails with `Error::UnknownFields` if the response has fields `Quote`
+/// does not model, to catch API schema drift
+pub async fn quote_strict(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    quote_config: QuoteConfig,
+) -> Result<Quote> {
+    quote_strict_with_base_url(
+        &quote_api_url(),
+        input_mint,
+        output_mint,
+        amount,
+        quote_config,
+    )
+    .await
+}
+
+/// Like `quote_strict()`, but against the quote API at `base_url` instead of `QUOTE_API_URL`
+pub async fn quote_strict_with_base_url(
+    base_url: &str,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    quote_config: QuoteConfig,
+) -> Result<Quote> {
+    quote_impl(
+        base_url,
+        input_mint,
+        output_mint,
+        amount,
+        quote_config,
+        true,
+    )
+    .await
+}
+
+async fn quote_impl(
+    base_url: &str,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    mut quote_config: QuoteConfig,
+    strict: bool,
+) -> Result<Quote> {
+    if let Some(exclude_program_ids) = quote_config.exclude_program_ids.take() {
+        let program_id_to_label = cached_program_id_to_label().await?;
+        let exclude_dexes = quote_config.exclude_dexes.get_or_insert_with(Vec::new);
+        for program_id in exclude_program_ids {
+            let label = program_id_to_label.get(&program_id).ok_or_else(|| {
+                Error::InvalidConfig(format!("no DEX label for program id {program_id}"))
+            })?;
+            if !exclude_dexes.contains(label) {
+                exclude_dexes.push(label.clone());
+            }
+        }
+    }
+    if quote_config.validate_dexes {
+        let supported_dexes = supported_dexes().await?;
+        if let Some(dex) = quote_config
+            .dexes
+            .iter()
+            .chain(&quote_config.exclude_dexes)
+            .flatten()
+            .find(|dex| !supported_dexes.contains(dex))
+        {
+            return Err(Error::InvalidConfig(format!("unknown DEX label `{dex}`")));
+        }
+    }
+    let url = build_quote_url(base_url, &input_mint, &output_mint, amount, &quote_config)?;
+
+    let quote: Quote = if strict {
+        let response: serde_json::Value = send(http_client().get(url)).await?;
+        let quote = serde_json::from_value(response.clone())?;
+        let mut unknown_fields = vec![];
+        find_unknown_fields(
+            &response,
+            &serde_json::to_value(&quote)?,
+            "",
+            &mut unknown_fields,
+        );
+        if !unknown_fields.is_empty() {
+            return Err(Error::UnknownFields(unknown_fields));
+        }
+        quote
+    } else {
+        send(http_client().get(url)).await?
+    };
+
+    if let Some(intermediate_tokens) = &quote_config.intermediate_tokens {
+        if !intermediate_tokens
+            .iter()
+            .any(|mint| quote.routes_through(mint))
+        {
+            return Err(Error::IntermediateTokenNotInRoute);
+        }
+    }
+    Ok(quote)
+}
+
+/// Collects the paths of object fields in `received` that are missing from `modeled`, the
+/// re-serialization of what was deserialized from it
+fn find_unknown_fields(
+    received: &serde_json::Value,
+    modeled: &serde_json::Value,
+    path: &str,
+    unknown_fields: &mut Vec<String>,
+) {
+    match (received, modeled) {
+        (serde_json::Value::Object(received), serde_json::Value::Object(modeled)) => {
+            for (key, value) in received {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match modeled.get(key) {
+                    Some(modeled) => find_unknown_fields(value, modeled, &path, unknown_fields),
+                    None => unknown_fields.push(path),
+                }
+            }
+        }
+        (serde_json::Value::Array(received), serde_json::Value::Array(modeled)) => {
+            for (i, (value, modeled)) in received.iter().zip(modeled).enumerate() {
+                find_unknown_fields(value, modeled, &format!("{path}[{i}]"), unknown_fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Get quote for a given input mint, output mint, and UI amount of the input mint, converted to
+/// base units with `to_raw_amount()`
+///
+/// Fails with `Error::InvalidConfig` before any request if `ui_amount` is not a positive number
+pub async fn quote_ui(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    ui_amount: f64,
+    quote_config: QuoteConfig,
+) -> Result<Quote> {
+    check_ui_amount(ui_amount)?;
+    let amount = to_raw_amount(&input_mint, ui_amount).await?;
+    quote(input_mint, output_mint, amount, quote_config).await
 }
 
-#[derive(Debug)]
+/// Get quote for the given token symbols and UI amount of the input token, resolving symbols
+/// with `TokenList::resolve_symbol()` on the cached token list
+pub async fn quote_by_symbol(
+    input_symbol: &str,
+    output_symbol: &str,
+    ui_amount: f64,
+    quote_config: QuoteConfig,
+) -> Result<Quote> {
+    let token_list = cached_token_list().await?;
+    let input_token = token_list.resolve_symbol(input_symbol)?;
+    let output_token = token_list.resolve_symbol(output_symbol)?;
+
+    quote_ui(
+        input_token.address,
+        output_token.address,
+        ui_amount,
+        quote_config,
+    )
+    .await
+}
+
+/// Stream of quotes fetched every `interval`. A quote is skipped when its `out_amount` is
+/// unchanged from the last one yielded. Errors are yielded without ending the stream.
+///
+/// A zero `interval` yields a single `Error::InvalidConfig` and ends the stream.
+pub fn quote_stream(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    quote_config: QuoteConfig,
+    interval: Duration,
+) -> impl Stream<Item = Result<Quote>> {
+    if interval.is_zero() {
+        return futures_util::stream::once(async {
+            Err(Error::InvalidConfig(
+                "quote stream interval must be non-zero".into(),
+            ))
+        })
+        .left_stream();
+    }
+
+    futures_util::stream::unfold(
+        (None, None),
+        move |(period, last_out_amount): (Option<tokio::time::Interval>, _)| {
+            let quote_config = quote_config.clone();
+            async move {
+                let mut period = period.unwrap_or_else(|| {
+                    let mut period = tokio::time::interval(interval);
+                    period.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    period
+                });
+                loop {
+                    period.tick().await;
+                    match quote(input_mint, output_mint, amount, quote_config.clone()).await {
+                        Ok(quote) if Some(quote.out_amount) == last_out_amount => continue,
+                        Ok(quote) => {
+                            let out_amount = quote.out_amount;
+                            return Some((Ok(quote), (Some(period), Some(out_amount))));
+                        }
+                        Err(err) => return Some((Err(err), (Some(period), last_out_amount))),
+                    }
+                }
+            }
+        },
+    )
+    .right_stream()
+}
+
+/// Get one quote per output mint for splitting `total_amount` of the input mint across them by
+/// weight. Weights must be positive and sum to 1.0; any rounding remainder goes to the last
+/// output. Fails with `Error::InvalidConfig` rather than quoting a zero amount if a weight is
+/// too small for its output to get any of `total_amount`.
+pub async fn split_swap(
+    input_mint: Pubkey,
+    outputs: &[(Pubkey, f64)],
+    total_amount: u64,
+    quote_config: QuoteConfig,
+) -> Result<Vec<Quote>> {
+    if let Some((output_mint, weight)) = outputs
+        .iter()
+        .find(|(_, weight)| !(weight.is_finite() && *weight > 0.))
+    {
+        return Err(Error::InvalidConfig(format!(
+            "split weight for {output_mint} must be finite and positive, got {weight}"
+        )));
+    }
+    let total_weight: f64 = outputs.iter().map(|(_, weight)| weight).sum();
+    if outputs.is_empty() || (total_weight - 1.).abs() > 1e-6 {
+        return Err(Error::InvalidConfig(format!(
+            "split weights must sum to 1.0, got {total_weight}"
+        )));
+    }
+
+    let mut remaining_amount = total_amount;
+    let mut amounts = Vec::with_capacity(outputs.len());
+    for (i, (output_mint, weight)) in outputs.iter().enumerate() {
+        let amount = if i == outputs.len() - 1 {
+            remaining_amount
+        } else {
+            ((total_amount as f64 * weight) as u64).min(remaining_amount)
+        };
+        if amount == 0 {
+            return Err(Error::InvalidConfig(format!(
+                "split weight {weight} for {output_mint} leaves it none of {total_amount}"
+            )));
+        }
+        remaining_amount -= amount;
+        amounts.push((*output_mint, amount));
+    }
+
+    let mut quotes = Vec::with_capacity(outputs.len());
+    for (output_mint, amount) in amounts {
+        quotes.push(quote(input_mint, output_mint, amount, quote_config.clone()).await?);
+    }
+    Ok(quotes)
+}
+
+#[derive(Clone, Debug)]
 pub enum PrioritizationFeeLamports {
     Auto,
-    Exact { lamports: u64 },
+    /// Total prioritization fee in lamports, which Jupiter divides by the compute unit limit it
+    /// picks to set the compute unit price
+    Exact {
+        lamports: u64,
+    },
+    /// Compute unit price in micro-lamports per compute unit, set as is
+    ComputeUnitPrice {
+        micro_lamports: u64,
+    },
+    /// Fee estimated for the given priority level, capped at `max_lamports`
+    PriorityLevelWithMaxLamports {
+        priority_level: PriorityLevel,
+        max_lamports: u64,
+    },
+    /// Tip paid to a Jito validator instead of a compute unit price
+    JitoTipLamports {
+        lamports: u64,
+    },
+}
+
+impl FromStr for PrioritizationFeeLamports {
+    type Err = Error;
+
+    /// Parses `auto`, `exact:<lamports>`, `cu_price:<micro lamports>`, `jito:<lamports>` or
+    /// `level:<medium|high|veryHigh>:<max lamports>`
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::ParsePrioritizationFeeLamports { value: s.into() };
+        let parse_lamports = |lamports: &str| lamports.parse::<u64>().map_err(|_| err());
+
+        match s.split(':').collect::<Vec<_>>()[..] {
+            ["auto"] => Ok(Self::Auto),
+            ["exact", lamports] => Ok(Self::Exact {
+                lamports: parse_lamports(lamports)?,
+            }),
+            ["cu_price", micro_lamports] => Ok(Self::ComputeUnitPrice {
+                micro_lamports: parse_lamports(micro_lamports)?,
+            }),
+            ["jito", lamports] => Ok(Self::JitoTipLamports {
+                lamports: parse_lamports(lamports)?,
+            }),
+            ["level", priority_level, max_lamports] => Ok(Self::PriorityLevelWithMaxLamports {
+                priority_level: priority_level.parse().map_err(|_| err())?,
+                max_lamports: parse_lamports(max_lamports)?,
+            }),
+            _ => Err(err()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum PriorityLevel {
+    Medium,
+    High,
+    VeryHigh,
+}
+
+impl FromStr for PriorityLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Medium" | "medium" => Ok(Self::Medium),
+            "High" | "high" => Ok(Self::High),
+            "VeryHigh" | "veryHigh" => Ok(Self::VeryHigh),
+            _ => Err(Error::ParsePriorityLevel { value: s.into() }),
+        }
+    }
+}
+
+impl fmt::Display for PriorityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+            Self::VeryHigh => write!(f, "veryHigh"),
+        }
+    }
+}
+
+/// Bounds for the slippage Jupiter may pick when simulating the swap
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicSlippage {
+    pub min_bps: u64,
+    pub max_bps: u64,
+}
+
+impl DynamicSlippage {
+    /// Creates new DynamicSlippage, checking that `min_bps <= max_bps <= 10000`
+    pub fn new(min_bps: u64, max_bps: u64) -> Result<Self> {
+        if min_bps > max_bps {
+            return Err(Error::InvalidConfig(format!(
+                "dynamic slippage min_bps ({min_bps}) exceeds max_bps ({max_bps})"
+            )));
+        }
+        if max_bps > 10_000 {
+            return Err(Error::InvalidConfig(format!(
+                "dynamic slippage max_bps ({max_bps}) exceeds 10000"
+            )));
+        }
+        Ok(Self { min_bps, max_bps })
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -284,40 +2173,155 @@ pub enum PrioritizationFeeLamports {
 pub struct SwapRequest {
     #[serde(with = "field_as_string")]
     pub user_public_key: Pubkey,
+    /// When true, SOL is wrapped before and unwrapped after the swap. When false, the swap uses
+    /// the user's wSOL token account for both input and output. The API has no wrap-only mode.
     pub wrap_and_unwrap_sol: Option<bool>,
     pub use_shared_accounts: Option<bool>,
     #[serde(with = "field_pubkey::option")]
     pub fee_account: Option<Pubkey>,
+    /// Ignored unless `prioritization_fee_lamports` is `Auto`. Prefer
+    /// `PrioritizationFeeLamports::ComputeUnitPrice`.
     #[deprecated = "please use SwapRequest::prioritization_fee_lamports instead"]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub compute_unit_price_micro_lamports: Option<u64>,
     #[serde(with = "field_prioritization_fee")]
     pub prioritization_fee_lamports: PrioritizationFeeLamports,
+    /// Must match `QuoteConfig::as_legacy_transaction` of the quote being swapped
     pub as_legacy_transaction: Option<bool>,
     pub use_token_ledger: Option<bool>,
     #[serde(with = "field_pubkey::option")]
     pub destination_token_account: Option<Pubkey>,
+    pub dynamic_slippage: Option<DynamicSlippage>,
     pub quote_response: Quote,
+    /// Additional body fields, for API options not modeled here. Fields above take precedence
+    /// on a key collision unless they are unset.
+    #[serde(skip)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl SwapRequest {
     /// Creates new SwapRequest with the given and default values
     pub fn new(user_public_key: Pubkey, quote_response: Quote) -> Self {
-        #[allow(deprecated)]
-        SwapRequest {
+        SwapRequestBuilder::new(user_public_key).build(quote_response)
+    }
+
+    fn body(&self) -> Result<serde_json::Value> {
+        let mut body = serde_json::to_value(self)?;
+        if let Some(body) = body.as_object_mut() {
+            // An explicit prioritization fee overrides the deprecated compute unit price, which
+            // remains the API's only way to set the compute unit price exactly
+            match self.prioritization_fee_lamports {
+                PrioritizationFeeLamports::Auto => {}
+                PrioritizationFeeLamports::ComputeUnitPrice { micro_lamports } => {
+                    body.remove("prioritizationFeeLamports");
+                    if let Some(deprecated) = body.insert(
+                        "computeUnitPriceMicroLamports".into(),
+                        micro_lamports.into(),
+                    ) {
+                        log::debug!(
+                            "ignoring deprecated compute_unit_price_micro_lamports {deprecated} \
+                             in favor of prioritization_fee_lamports"
+                        );
+                    }
+                }
+                _ => {
+                    if let Some(deprecated) = body.remove("computeUnitPriceMicroLamports") {
+                        log::debug!(
+                            "ignoring deprecated compute_unit_price_micro_lamports {deprecated} \
+                             in favor of prioritization_fee_lamports"
+                        );
+                    }
+                }
+            }
+            for (key, value) in &self.extra {
+                let field = body.entry(key).or_insert(serde_json::Value::Null);
+                if field.is_null() {
+                    *field = value.clone();
+                }
+            }
+        }
+        Ok(body)
+    }
+
+    /// Lets Jupiter pick the slippage, up to `max_bps`, such as the `slippage_bps` of the quote
+    pub fn dynamic_slippage_capped_at(mut self, max_bps: u64) -> Result<Self> {
+        self.dynamic_slippage = Some(DynamicSlippage::new(0, max_bps)?);
+        Ok(self)
+    }
+
+    /// Collects the platform fee into the Jupiter referral token account of `referral_account`
+    /// for `mint`, which must be the input or output mint of the quote
+    pub fn with_referral(mut self, referral_account: &Pubkey, mint: &Pubkey) -> Self {
+        self.fee_account = Some(referral_fee_account(referral_account, mint));
+        self
+    }
+}
+
+/// Swap request options without a quote, for configuring requests before the quote is
+/// available or reusing one configuration across many quotes
+#[derive(Clone, Debug)]
+pub struct SwapRequestBuilder {
+    pub user_public_key: Pubkey,
+    pub wrap_and_unwrap_sol: Option<bool>,
+    pub use_shared_accounts: Option<bool>,
+    pub fee_account: Option<Pubkey>,
+    pub prioritization_fee_lamports: PrioritizationFeeLamports,
+    pub as_legacy_transaction: Option<bool>,
+    pub use_token_ledger: Option<bool>,
+    pub destination_token_account: Option<Pubkey>,
+    pub dynamic_slippage: Option<DynamicSlippage>,
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SwapRequestBuilder {
+    /// Creates new SwapRequestBuilder with the given and default values
+    pub fn new(user_public_key: Pubkey) -> Self {
+        Self {
             user_public_key,
             wrap_and_unwrap_sol: Some(true),
             use_shared_accounts: Some(true),
             fee_account: None,
-            compute_unit_price_micro_lamports: None,
             prioritization_fee_lamports: PrioritizationFeeLamports::Auto,
             as_legacy_transaction: Some(false),
             use_token_ledger: Some(false),
             destination_token_account: None,
+            dynamic_slippage: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    /// Creates a SwapRequest for `quote_response` with these options
+    pub fn build(&self, quote_response: Quote) -> SwapRequest {
+        #[allow(deprecated)]
+        SwapRequest {
+            user_public_key: self.user_public_key,
+            wrap_and_unwrap_sol: self.wrap_and_unwrap_sol,
+            use_shared_accounts: self.use_shared_accounts,
+            fee_account: self.fee_account,
+            compute_unit_price_micro_lamports: None,
+            prioritization_fee_lamports: self.prioritization_fee_lamports.clone(),
+            as_legacy_transaction: self.as_legacy_transaction,
+            use_token_ledger: self.use_token_ledger,
+            destination_token_account: self.destination_token_account,
+            dynamic_slippage: self.dynamic_slippage.clone(),
             quote_response,
+            extra: self.extra.clone(),
         }
     }
 }
 
+const REFERRAL_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("REFER4ZgmyYx9c6He5XfaTMiGfdLwRnkV4RPp9t9iF3");
+
+/// Derives the Jupiter referral token account that receives platform fees in `mint`
+pub fn referral_fee_account(referral_account: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"referral_ata", referral_account.as_ref(), mint.as_ref()],
+        &REFERRAL_PROGRAM_ID,
+    )
+    .0
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SwapResponse {
@@ -327,48 +2331,219 @@ struct SwapResponse {
 
 /// Get swap serialized transactions for a quote
 pub async fn swap(swap_request: SwapRequest) -> Result<Swap> {
-    let url = format!("{}/swap", quote_api_url());
+    swap_with_base_url(&quote_api_url(), swap_request).await
+}
 
-    let response = maybe_jupiter_api_error::<SwapResponse>(
-        reqwest::Client::builder()
-            .build()?
-            .post(url)
-            .header("Accept", "application/json")
-            .json(&swap_request)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?,
-    )?;
+/// Like `swap()`, but against the swap API at `base_url` instead of `QUOTE_API_URL`
+pub async fn swap_with_base_url(base_url: &str, swap_request: SwapRequest) -> Result<Swap> {
+    let response = swap_response(base_url, swap_request).await?;
+    decode_swap(response, |transaction| {
+        Ok(bincode::deserialize(transaction)?)
+    })
+}
 
-    fn decode(base64_transaction: String) -> Result<VersionedTransaction> {
-        bincode::deserialize(&BASE64_STANDARD.decode(base64_transaction)?).map_err(|err| err.into())
-    }
+/// Like `swap()`, but deserializes the swap transaction from its wire bytes with `decode`
+/// instead of `bincode`
+pub async fn swap_with_decoder<F>(swap_request: SwapRequest, decode: F) -> Result<Swap>
+where
+    F: FnOnce(&[u8]) -> Result<VersionedTransaction>,
+{
+    swap_with_decoder_with_base_url(&quote_api_url(), swap_request, decode).await
+}
+
+/// Like `swap_with_decoder()`, but against the swap API at `base_url` instead of
+/// `QUOTE_API_URL`
+pub async fn swap_with_decoder_with_base_url<F>(
+    base_url: &str,
+    swap_request: SwapRequest,
+    decode: F,
+) -> Result<Swap>
+where
+    F: FnOnce(&[u8]) -> Result<VersionedTransaction>,
+{
+    let response = swap_response(base_url, swap_request).await?;
+    decode_swap(response, decode)
+}
+
+fn decode_swap<F>(response: SwapResponse, decode: F) -> Result<Swap>
+where
+    F: FnOnce(&[u8]) -> Result<VersionedTransaction>,
+{
+    let swap_transaction = BASE64_STANDARD
+        .decode(response.swap_transaction)
+        .map_err(Error::from)
+        .and_then(|transaction| decode(&transaction))
+        .map_err(|err| Error::TransactionDecode {
+            source: Box::new(err),
+        })?;
 
     Ok(Swap {
-        swap_transaction: decode(response.swap_transaction)?,
+        swap_transaction,
         last_valid_block_height: response.last_valid_block_height,
     })
 }
 
-/// Get swap serialized transaction instructions for a quote
-pub async fn swap_instructions(swap_request: SwapRequest) -> Result<SwapInstructions> {
-    let url = format!("{}/swap-instructions", quote_api_url());
-
-    let response = reqwest::Client::builder()
-        .build()?
-        .post(url)
-        .header("Accept", "application/json")
-        .json(&swap_request)
-        .send()
+/// Like `swap()`, but returns the base64 encoded swap transaction as is, along with its last
+/// valid block height, for relaying it without decoding
+pub async fn swap_raw(swap_request: SwapRequest) -> Result<(String, u64)> {
+    swap_raw_with_base_url(&quote_api_url(), swap_request).await
+}
+
+/// Like `swap_raw()`, but against the swap API at `base_url` instead of `QUOTE_API_URL`
+pub async fn swap_raw_with_base_url(
+    base_url: &str,
+    swap_request: SwapRequest,
+) -> Result<(String, u64)> {
+    let response = swap_response(base_url, swap_request).await?;
+    Ok((response.swap_transaction, response.last_valid_block_height))
+}
+
+async fn swap_response(base_url: &str, swap_request: SwapRequest) -> Result<SwapResponse> {
+    let url = format!("{base_url}/swap");
+
+    send(
+        http_client()
+            .post(url)
+            .header("Accept", "application/json")
+            .json(&swap_request.body()?),
+    )
+    .await
+}
+
+/// Quotes with decreasing `max_accounts`, starting from `quote_config.max_accounts` or 64,
+/// until the swap transaction built with `swap_request_builder` still fits in a packet after
+/// `extra_instructions` are added to it. Returns the first quote that fits, along with its swap
+pub async fn quote_fitting_transaction(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    quote_config: QuoteConfig,
+    swap_request_builder: &SwapRequestBuilder,
+    extra_instructions: &[Instruction],
+) -> Result<(Quote, Swap)> {
+    let mut max_accounts = quote_config.max_accounts.unwrap_or(64);
+    let mut smallest_size = usize::MAX;
+    while max_accounts > 0 {
+        let quote = quote(
+            input_mint,
+            output_mint,
+            amount,
+            QuoteConfig {
+                max_accounts: Some(max_accounts),
+                ..quote_config.clone()
+            },
+        )
         .await?;
+        let swap = swap(swap_request_builder.build(quote.clone())).await?;
+
+        let size = swap.serialized_size_with(extra_instructions)?;
+        if size <= MAX_TRANSACTION_SIZE {
+            return Ok((quote, swap));
+        }
+        smallest_size = smallest_size.min(size);
+        max_accounts = max_accounts.saturating_sub(8);
+    }
+    Err(Error::TransactionTooLarge {
+        size: smallest_size,
+    })
+}
+
+/// Splits `total_amount` into `chunks` swaps, quoting and building each one for the next of
+/// `payers` in turn. Each swap request is built from `swap_request_builder` with only its
+/// `user_public_key` replaced by the payer. The remainder of the split is added to the last
+/// chunk, so the chunks' amounts add up to `total_amount` exactly
+pub async fn chunked_swaps(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    total_amount: u64,
+    chunks: u64,
+    quote_config: QuoteConfig,
+    swap_request_builder: &SwapRequestBuilder,
+    payers: &[Pubkey],
+) -> Result<Vec<Swap>> {
+    chunked_swaps_with_base_url(
+        &quote_api_url(),
+        input_mint,
+        output_mint,
+        total_amount,
+        chunks,
+        quote_config,
+        swap_request_builder,
+        payers,
+    )
+    .await
+}
+
+/// Like `chunked_swaps()`, but against the quote and swap API at `base_url` instead of
+/// `QUOTE_API_URL`
+#[allow(clippy::too_many_arguments)]
+pub async fn chunked_swaps_with_base_url(
+    base_url: &str,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    total_amount: u64,
+    chunks: u64,
+    quote_config: QuoteConfig,
+    swap_request_builder: &SwapRequestBuilder,
+    payers: &[Pubkey],
+) -> Result<Vec<Swap>> {
+    if chunks == 0 || payers.is_empty() {
+        return Err(Error::InvalidConfig(
+            "chunked_swaps requires at least one chunk and one payer".into(),
+        ));
+    }
+    let chunk_amount = total_amount / chunks;
+    if chunk_amount == 0 {
+        return Err(Error::InvalidConfig(format!(
+            "{total_amount} cannot be split into {chunks} non-zero chunks"
+        )));
+    }
 
-    if !response.status().is_success() {
-        return Err(Error::JupiterApi(response.text().await?));
+    let mut swaps = vec![];
+    for (chunk, payer) in (0..chunks).zip(payers.iter().cycle()) {
+        let amount = if chunk == chunks - 1 {
+            chunk_amount + total_amount % chunks
+        } else {
+            chunk_amount
+        };
+        let quote = quote_with_base_url(
+            base_url,
+            input_mint,
+            output_mint,
+            amount,
+            quote_config.clone(),
+        )
+        .await?;
+        let swap_request = SwapRequestBuilder {
+            user_public_key: *payer,
+            ..swap_request_builder.clone()
+        }
+        .build(quote);
+        swaps.push(swap_with_base_url(base_url, swap_request).await?);
     }
+    Ok(swaps)
+}
 
-    Ok(response.json::<SwapInstructions>().await?)
+/// Get swap serialized transaction instructions for a quote
+pub async fn swap_instructions(swap_request: SwapRequest) -> Result<SwapInstructions> {
+    swap_instructions_with_base_url(&quote_api_url(), swap_request).await
+}
+
+/// Like `swap_instructions()`, but against the swap API at `base_url` instead of
+/// `QUOTE_API_URL`
+pub async fn swap_instructions_with_base_url(
+    base_url: &str,
+    swap_request: SwapRequest,
+) -> Result<SwapInstructions> {
+    let url = format!("{base_url}/swap-instructions");
+
+    send(
+        http_client()
+            .post(url)
+            .header("Accept", "application/json")
+            .json(&swap_request.body()?),
+    )
+    .await
 }
 
 /// Returns a hash map, input mint as key and an array of valid output mint as values
@@ -385,7 +2560,7 @@ pub async fn route_map() -> Result<RouteMap> {
         indexed_route_map: HashMap<usize, Vec<usize>>,
     }
 
-    let response = reqwest::get(url).await?.json::<IndexedRouteMap>().await?;
+    let response = send::<IndexedRouteMap>(http_client().get(url)).await?;
 
     let mint_keys = response
         .mint_keys
@@ -403,3 +2578,118 @@ pub async fn route_map() -> Result<RouteMap> {
 
     Ok(route_map)
 }
+
+/// Returns a hash map, DEX program id as key and the DEX label as value
+pub async fn program_id_to_label() -> Result<ProgramIdToLabel> {
+    program_id_to_label_with_base_url(&quote_api_url()).await
+}
+
+/// Like `program_id_to_label()`, but against the quote API at `base_url` instead of
+/// `QUOTE_API_URL`
+pub async fn program_id_to_label_with_base_url(base_url: &str) -> Result<ProgramIdToLabel> {
+    let url = format!("{base_url}/program-id-to-label");
+
+    let response: HashMap<String, String> = send(http_client().get(url)).await?;
+
+    response
+        .into_iter()
+        .map(|(program_id, label)| Ok((program_id.parse::<Pubkey>()?, label)))
+        .collect()
+}
+
+/// Like `program_id_to_label()`, but cached for the life of the process
+pub async fn cached_program_id_to_label() -> Result<&'static ProgramIdToLabel> {
+    static PROGRAM_ID_TO_LABEL: tokio::sync::OnceCell<ProgramIdToLabel> =
+        tokio::sync::OnceCell::const_new();
+
+    PROGRAM_ID_TO_LABEL
+        .get_or_try_init(program_id_to_label)
+        .await
+}
+
+/// Returns the sorted DEX labels accepted by `QuoteConfig::dexes` and `exclude_dexes`, using the
+/// cached label map
+pub async fn supported_dexes() -> Result<Vec<String>> {
+    Ok(cached_program_id_to_label()
+        .await?
+        .values()
+        .cloned()
+        .sorted()
+        .dedup()
+        .collect())
+}
+
+/// Returns the labels of only the given DEX program ids, using the cached label map
+pub async fn program_id_to_label_for(program_ids: &[Pubkey]) -> Result<ProgramIdToLabel> {
+    let program_id_to_label = cached_program_id_to_label().await?;
+
+    Ok(program_ids
+        .iter()
+        .filter_map(|program_id| {
+            program_id_to_label
+                .get(program_id)
+                .map(|label| (*program_id, label.clone()))
+        })
+        .collect())
+}
+
+/// Returns metadata for all tradable tokens
+pub async fn tokens_detailed() -> Result<Vec<Token>> {
+    tokens_detailed_with_base_url(&tokens_api_url()).await
+}
+
+/// Like `tokens_detailed()`, but against the token API at `base_url` instead of
+/// `TOKENS_API_URL`
+pub async fn tokens_detailed_with_base_url(base_url: &str) -> Result<Vec<Token>> {
+    let url = format!("{base_url}/tokens_with_markets");
+
+    // The token list has been served both as an array and as an object keyed by mint
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TokensResponse {
+        List(Vec<Token>),
+        ByMint(HashMap<String, Token>),
+    }
+
+    let response = send_with_blocking_parse(http_client().get(url)).await?;
+    Ok(match response {
+        TokensResponse::List(tokens) => tokens,
+        TokensResponse::ByMint(tokens) => tokens.into_values().collect(),
+    })
+}
+
+/// Returns recently listed tokens, newest first, with `Token::created_at` set
+pub async fn new_tokens() -> Result<Vec<Token>> {
+    let url = format!("{}/new", tokens_api_url());
+
+    send(http_client().get(url)).await
+}
+
+/// Returns all tradable tokens, indexed by mint and symbol
+pub async fn token_list() -> Result<TokenList> {
+    Ok(tokens_detailed().await?.into())
+}
+
+/// Like `token_list()`, but cached for the life of the process
+pub async fn cached_token_list() -> Result<&'static TokenList> {
+    static TOKEN_LIST: tokio::sync::OnceCell<TokenList> = tokio::sync::OnceCell::const_new();
+
+    TOKEN_LIST.get_or_try_init(token_list).await
+}
+
+/// Returns the decimals of `mint`, using the cached token list
+pub async fn decimals(mint: &Pubkey) -> Result<u8> {
+    cached_token_list().await?.decimals(mint)
+}
+
+/// Converts `ui_amount` of `mint` to base units with `TokenList::to_raw_amount()` on the cached
+/// token list
+pub async fn to_raw_amount(mint: &Pubkey, ui_amount: f64) -> Result<u64> {
+    cached_token_list().await?.to_raw_amount(mint, ui_amount)
+}
+
+/// Converts `amount` base units of `mint` to whole tokens with `TokenList::to_ui_amount()` on
+/// the cached token list
+pub async fn to_ui_amount(mint: &Pubkey, amount: u64) -> Result<f64> {
+    cached_token_list().await?.to_ui_amount(mint, amount)
+}
@@ -0,0 +1,310 @@
+//! Minimal Solana JSON-RPC helpers for landing swap transactions, enabled by the `rpc` feature
+use {
+    crate::{Error, Result},
+    base64::prelude::{Engine as _, BASE64_STANDARD},
+    serde::{de::DeserializeOwned, Deserialize},
+    serde_json::json,
+    solana_sdk::{
+        address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+        hash::Hash,
+        pubkey::Pubkey,
+        signature::Signature,
+        signers::Signers,
+        transaction::VersionedTransaction,
+    },
+    std::{env, time::Duration},
+};
+
+fn rpc_url() -> String {
+    env::var("RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string())
+}
+
+/// Where and how to send a transaction
+#[derive(Clone, Debug)]
+pub struct SendConfig {
+    /// JSON-RPC endpoint that accepts `sendTransaction`, defaults to `RPC_URL`
+    pub url: String,
+    pub skip_preflight: bool,
+    pub max_retries: Option<usize>,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            url: rpc_url(),
+            skip_preflight: false,
+            max_retries: None,
+        }
+    }
+}
+
+async fn rpc_request<T>(url: &str, method: &str, params: serde_json::Value) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let response = crate::http_client()
+        .post(url)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await?;
+
+    let mut response = match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(response) if status.is_success() => response,
+        // Nodes report rate limits and the like with an error status, not always with a
+        // JSON-RPC error body
+        response => {
+            let message = response
+                .ok()
+                .and_then(|response| error_message(&response))
+                .unwrap_or(body);
+            return Err(Error::Rpc(format!("{method} ({status}): {message}")));
+        }
+    };
+
+    if let Some(message) = error_message(&response) {
+        return Err(Error::Rpc(message));
+    }
+    Ok(serde_json::from_value(response["result"].take())?)
+}
+
+/// The message of the JSON-RPC error in `response`, if it has one
+fn error_message(response: &serde_json::Value) -> Option<String> {
+    let error = response.get("error")?;
+    Some(
+        error
+            .get("message")
+            .and_then(|message| message.as_str())
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| error.to_string()),
+    )
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    value: T,
+}
+
+/// Fetch the latest blockhash and the last block height at which it is valid
+pub async fn get_latest_blockhash(rpc_url: &str) -> Result<(Hash, u64)> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct LatestBlockhash {
+        blockhash: String,
+        last_valid_block_height: u64,
+    }
+
+    let RpcResponse {
+        value:
+            LatestBlockhash {
+                blockhash,
+                last_valid_block_height,
+            },
+    } = rpc_request(
+        rpc_url,
+        "getLatestBlockhash",
+        json!([{"commitment": "confirmed"}]),
+    )
+    .await?;
+
+    let blockhash = blockhash
+        .parse()
+        .map_err(|err| Error::Rpc(format!("invalid blockhash `{blockhash}`: {err}")))?;
+    Ok((blockhash, last_valid_block_height))
+}
+
+/// Set `recent_blockhash` on `transaction` and sign it with `signers`
+pub fn sign_transaction<T>(
+    transaction: VersionedTransaction,
+    recent_blockhash: Hash,
+    signers: &T,
+) -> Result<VersionedTransaction>
+where
+    T: Signers + ?Sized,
+{
+    let mut message = transaction.message;
+    message.set_recent_blockhash(recent_blockhash);
+    Ok(VersionedTransaction::try_new(message, signers)?)
+}
+
+/// Like `sign_transaction()`, but fetches the latest blockhash first
+pub async fn refresh_blockhash_and_sign<T>(
+    rpc_url: &str,
+    transaction: VersionedTransaction,
+    signers: &T,
+) -> Result<VersionedTransaction>
+where
+    T: Signers + ?Sized,
+{
+    let (recent_blockhash, _) = get_latest_blockhash(rpc_url).await?;
+    sign_transaction(transaction, recent_blockhash, signers)
+}
+
+/// Result of simulating a transaction
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransactionResult {
+    pub err: Option<serde_json::Value>,
+    pub logs: Option<Vec<String>>,
+    pub units_consumed: Option<u64>,
+}
+
+/// Simulate a transaction, replacing its blockhash so it need not be freshly signed
+pub async fn simulate_transaction(
+    rpc_url: &str,
+    transaction: &VersionedTransaction,
+) -> Result<SimulateTransactionResult> {
+    let RpcResponse { value } = rpc_request(
+        rpc_url,
+        "simulateTransaction",
+        json!([
+            BASE64_STANDARD.encode(bincode::serialize(transaction)?),
+            {
+                "encoding": "base64",
+                "commitment": "confirmed",
+                "replaceRecentBlockhash": true,
+                "sigVerify": false,
+            }
+        ]),
+    )
+    .await?;
+    Ok(value)
+}
+
+/// Fetch the address lookup tables referenced by `SwapInstructions::address_lookup_table_addresses`
+pub async fn get_address_lookup_tables(
+    rpc_url: &str,
+    addresses: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>> {
+    #[derive(Deserialize)]
+    struct Account {
+        data: (String, String),
+    }
+
+    let RpcResponse { value: accounts } = rpc_request::<RpcResponse<Vec<Option<Account>>>>(
+        rpc_url,
+        "getMultipleAccounts",
+        json!([
+            addresses.iter().map(|address| address.to_string()).collect::<Vec<_>>(),
+            {"encoding": "base64", "commitment": "confirmed"}
+        ]),
+    )
+    .await?;
+
+    addresses
+        .iter()
+        .zip(accounts)
+        .map(|(address, account)| {
+            let account = account
+                .ok_or_else(|| Error::Rpc(format!("address lookup table {address} not found")))?;
+            let data = BASE64_STANDARD.decode(account.data.0)?;
+            let table = AddressLookupTable::deserialize(&data).map_err(|err| {
+                Error::Rpc(format!("invalid address lookup table {address}: {err}"))
+            })?;
+            Ok(AddressLookupTableAccount {
+                key: *address,
+                addresses: table.addresses.to_vec(),
+            })
+        })
+        .collect()
+}
+
+/// Send a signed transaction, returning its signature
+pub async fn send_transaction(
+    transaction: &VersionedTransaction,
+    send_config: SendConfig,
+) -> Result<Signature> {
+    let signature: String = rpc_request(
+        &send_config.url,
+        "sendTransaction",
+        json!([
+            BASE64_STANDARD.encode(bincode::serialize(transaction)?),
+            {
+                "encoding": "base64",
+                "skipPreflight": send_config.skip_preflight,
+                "maxRetries": send_config.max_retries,
+            }
+        ]),
+    )
+    .await?;
+
+    signature
+        .parse()
+        .map_err(|err| Error::Rpc(format!("invalid signature `{signature}`: {err}")))
+}
+
+/// Whether `signature` is confirmed, failing if the transaction failed. With
+/// `search_transaction_history` the node also looks past its recent status cache.
+async fn signature_confirmed(
+    rpc_url: &str,
+    signature: &Signature,
+    search_transaction_history: bool,
+) -> Result<bool> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct SignatureStatus {
+        err: Option<serde_json::Value>,
+        confirmation_status: Option<String>,
+    }
+
+    let RpcResponse { value: statuses } = rpc_request::<RpcResponse<Vec<Option<SignatureStatus>>>>(
+        rpc_url,
+        "getSignatureStatuses",
+        json!([
+            [signature.to_string()],
+            {"searchTransactionHistory": search_transaction_history}
+        ]),
+    )
+    .await?;
+
+    match statuses.into_iter().next().flatten() {
+        Some(SignatureStatus { err: Some(err), .. }) => {
+            Err(Error::Rpc(format!("transaction {signature} failed: {err}")))
+        }
+        Some(status) => Ok(matches!(
+            status.confirmation_status.as_deref(),
+            Some("confirmed" | "finalized")
+        )),
+        None => Ok(false),
+    }
+}
+
+/// Wait until `signature` is confirmed, or return `Error::Expired` once the block height passes
+/// `last_valid_block_height` (see `Swap::last_valid_block_height`) and a final status check,
+/// searching the transaction history, still does not find it confirmed
+pub async fn confirm_swap(
+    rpc_url: &str,
+    signature: &Signature,
+    last_valid_block_height: u64,
+) -> Result<()> {
+    loop {
+        if signature_confirmed(rpc_url, signature, false).await? {
+            return Ok(());
+        }
+
+        let block_height: u64 = rpc_request(
+            rpc_url,
+            "getBlockHeight",
+            json!([{"commitment": "confirmed"}]),
+        )
+        .await?;
+        if block_height > last_valid_block_height {
+            // The transaction may have landed between the status check and the block height
+            // passing `last_valid_block_height`
+            if signature_confirmed(rpc_url, signature, true).await? {
+                return Ok(());
+            }
+            return Err(Error::Expired {
+                signature: *signature,
+            });
+        }
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
@@ -0,0 +1,1057 @@
+//! Replays recorded API responses from `tests/fixtures` through the public API, so schema drift
+//! shows up as a test failure without hitting the network.
+//!
+//! To refresh the fixtures from the live API:
+//! ```sh
+//! $ JUP_AG_RECORD_FIXTURES=1 cargo test --test replay
+//! ```
+
+use {
+    jup_ag::{QuoteConfig, SwapRequest},
+    solana_compute_budget_interface::ComputeBudgetInstruction,
+    solana_sdk::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{v0, AddressLookupTableAccount, VersionedMessage},
+        pubkey,
+        pubkey::Pubkey,
+        transaction::VersionedTransaction,
+    },
+    std::{env, path::PathBuf, sync::OnceLock},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    },
+};
+
+const SOL: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+const USDC: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+const USER: Pubkey = pubkey!("5YNmS1R9nNSCDzb5a7mMJ1dwK9uHeAAF4CmPEwKgVWr8");
+
+struct Upstream {
+    quote_api_url: String,
+    price_api_url: String,
+    tokens_api_url: String,
+}
+
+fn recording() -> bool {
+    env::var_os("JUP_AG_RECORD_FIXTURES").is_some()
+}
+
+fn upstream() -> &'static Upstream {
+    static UPSTREAM: OnceLock<Upstream> = OnceLock::new();
+
+    UPSTREAM.get_or_init(|| Upstream {
+        quote_api_url: env::var("QUOTE_API_URL")
+            .unwrap_or_else(|_| "https://quote-api.jup.ag/v6".into()),
+        price_api_url: env::var("PRICE_API_URL")
+            .unwrap_or_else(|_| "https://price.jup.ag/v1".into()),
+        tokens_api_url: env::var("TOKENS_API_URL")
+            .unwrap_or_else(|_| "https://tokens.jup.ag".into()),
+    })
+}
+
+/// Starts a fixture server, returning its base URL and a receiver for the target and body of
+/// each request it serves
+fn fixture_server() -> (String, std::sync::mpsc::Receiver<(String, String)>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (request_sender, request_receiver) = std::sync::mpsc::channel();
+    let upstream = upstream();
+
+    // The server gets its own runtime, as each test's runtime ends with the test
+    std::thread::spawn(move || {
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async move {
+                let listener = TcpListener::from_std(listener).unwrap();
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    let request_sender = request_sender.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = serve(stream, upstream, request_sender).await {
+                            eprintln!("fixture server: {err}");
+                        }
+                    });
+                }
+            })
+    });
+    (base_url, request_receiver)
+}
+
+/// Starts the fixture server once per test binary, returning its base URL for the crate's
+/// `*_with_base_url()` functions
+fn replay_server() -> &'static str {
+    static BASE_URL: OnceLock<String> = OnceLock::new();
+
+    BASE_URL.get_or_init(|| fixture_server().0)
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    upstream: &Upstream,
+    request_sender: std::sync::mpsc::Sender<(String, String)>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut request = Vec::new();
+    let header_len = loop {
+        let mut buf = [0; 4096];
+        let len = stream.read(&mut buf).await?;
+        if len == 0 {
+            return Err("connection closed mid-request".into());
+        }
+        request.extend_from_slice(&buf[..len]);
+        if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..header_len]).into_owned();
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>())
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut body = request.split_off(header_len);
+    while body.len() < content_length {
+        let mut buf = [0; 4096];
+        let len = stream.read(&mut buf).await?;
+        if len == 0 {
+            return Err("connection closed mid-body".into());
+        }
+        body.extend_from_slice(&buf[..len]);
+    }
+
+    // Nobody listens for the requests to the shared replay server
+    let _ = request_sender.send((target.clone(), String::from_utf8_lossy(&body).into_owned()));
+
+    let path = target.split('?').next().unwrap_or_default();
+    let (name, upstream_url) = match path {
+        "/quote" => ("quote", &upstream.quote_api_url),
+        "/swap" => ("swap", &upstream.quote_api_url),
+        "/swap-instructions" => ("swap_instructions", &upstream.quote_api_url),
+        "/program-id-to-label" => ("program_id_to_label", &upstream.quote_api_url),
+        "/tokens_with_markets" => ("tokens", &upstream.tokens_api_url),
+        "/price" => ("price", &upstream.price_api_url),
+        _ => return Err(format!("no fixture for {method} {target}").into()),
+    };
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{name}.json"));
+
+    let response = if recording() {
+        let response = reqwest::Client::new()
+            .request(method.parse()?, format!("{upstream_url}{target}"))
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(body)
+            .send()
+            .await?
+            .bytes()
+            .await?;
+        let pretty =
+            serde_json::to_vec_pretty(&serde_json::from_slice::<serde_json::Value>(&response)?)?;
+        tokio::fs::write(&fixture, &pretty).await?;
+        pretty
+    } else {
+        tokio::fs::read(&fixture).await?
+    };
+
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                response.len()
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.write_all(&response).await?;
+    Ok(())
+}
+
+/// Serves `body` with a 200 status to a single request, returning the server's base URL and a
+/// receiver for the request target
+fn serve_once(body: String) -> (String, std::sync::mpsc::Receiver<String>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (target_sender, target_receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let mut buf = [0; 4096];
+            let len = stream.read(&mut buf).unwrap();
+            if len == 0 {
+                return;
+            }
+            request.extend_from_slice(&buf[..len]);
+        }
+        let request = String::from_utf8_lossy(&request);
+        if let Some(target) = request.split_whitespace().nth(1) {
+            let _ = target_sender.send(target.to_string());
+        }
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    });
+    (base_url, target_receiver)
+}
+
+async fn price_served(body: &str) -> jup_ag::Result<jup_ag::Price> {
+    jup_ag::price_with_base_url(&serve_once(body.into()).0, SOL, USDC, 1.).await
+}
+
+#[tokio::test]
+async fn error_in_success_response() {
+    let body = format!(
+        r#"{{"id":"{SOL}","mintSymbol":"SOL","vsToken":"{USDC}","vsTokenSymbol":"USDC","price":145.5}}"#
+    );
+    assert_eq!(price_served(&body).await.unwrap().input_mint, SOL);
+
+    match price_served(r#"{"error":"Route not found"}"#).await {
+        Err(jup_ag::Error::JupiterApi {
+            status: None,
+            message,
+            code: None,
+        }) => assert_eq!(message, "Route not found"),
+        result => panic!("unexpected {result:?}"),
+    }
+
+    match price_served(r#"{"unexpected":true}"#).await {
+        Err(jup_ag::Error::SerdeJson(_)) => {}
+        result => panic!("unexpected {result:?}"),
+    }
+
+    let err = price_served(
+        r#"{"error":"Could not find any route","errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.jupiter_error_code(),
+        Some(&jup_ag::JupiterErrorCode::CouldNotFindAnyRoute)
+    );
+    assert!(
+        matches!(err, jup_ag::Error::JupiterApi { ref message, .. } if message == "Could not find any route")
+    );
+    assert_eq!(
+        err.to_string(),
+        "Jupiter API: Could not find any route (COULD_NOT_FIND_ANY_ROUTE)"
+    );
+}
+
+#[tokio::test]
+async fn quote() {
+    let base_url = replay_server();
+
+    let quote =
+        jup_ag::quote_with_base_url(base_url, SOL, USDC, 100_000_000, QuoteConfig::default())
+            .await
+            .unwrap();
+    assert_eq!(quote.input_mint, SOL);
+    assert_eq!(quote.output_mint, USDC);
+    assert!(!quote.route_plan.is_empty());
+    assert_eq!(quote.worst_case_out(), Some(quote.other_amount_threshold));
+    assert_eq!(quote.worst_case_in(), None);
+    assert!(!quote.is_estimate);
+
+    let estimate = quote.estimate_for_amount(200_000_000);
+    assert!(estimate.is_estimate);
+    assert_eq!(estimate.in_amount, 200_000_000);
+    assert_eq!(estimate.out_amount / 2, quote.out_amount);
+}
+
+#[tokio::test]
+async fn quote_strict_unknown_fields() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quote.json");
+    let mut quote =
+        serde_json::from_slice::<serde_json::Value>(&std::fs::read(fixture).unwrap()).unwrap();
+    let known = quote.to_string();
+    quote["newTopLevelField"] = true.into();
+    quote["routePlan"][0]["swapInfo"]["newSwapInfoField"] = 1.into();
+    let body = quote.to_string();
+
+    let (base_url, _) = serve_once(known);
+    jup_ag::quote_strict_with_base_url(&base_url, SOL, USDC, 100_000_000, QuoteConfig::default())
+        .await
+        .unwrap();
+
+    let (base_url, _) = serve_once(body.clone());
+    match jup_ag::quote_strict_with_base_url(
+        &base_url,
+        SOL,
+        USDC,
+        100_000_000,
+        QuoteConfig::default(),
+    )
+    .await
+    {
+        Err(jup_ag::Error::UnknownFields(mut paths)) => {
+            paths.sort();
+            assert_eq!(
+                paths,
+                ["newTopLevelField", "routePlan[0].swapInfo.newSwapInfoField"]
+            );
+        }
+        result => panic!("unexpected {result:?}"),
+    }
+
+    // `quote()` ignores the same unknown fields
+    let (base_url, _) = serve_once(body);
+    jup_ag::quote_with_base_url(&base_url, SOL, USDC, 100_000_000, QuoteConfig::default())
+        .await
+        .unwrap();
+}
+
+#[test]
+fn quote_context_slot_and_time_taken_null_or_missing() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quote.json");
+    let mut quote =
+        serde_json::from_slice::<serde_json::Value>(&std::fs::read(fixture).unwrap()).unwrap();
+    let quote = quote.as_object_mut().unwrap();
+
+    quote.insert("contextSlot".into(), serde_json::Value::Null);
+    quote.insert("timeTaken".into(), serde_json::Value::Null);
+    let null = serde_json::from_value::<jup_ag::Quote>(quote.clone().into()).unwrap();
+    assert_eq!(null.context_slot, None);
+    assert_eq!(null.time_taken, None);
+
+    quote.remove("contextSlot");
+    quote.remove("timeTaken");
+    let missing = serde_json::from_value::<jup_ag::Quote>(quote.clone().into()).unwrap();
+    assert_eq!(missing.context_slot, None);
+    assert_eq!(missing.time_taken, None);
+}
+
+#[tokio::test]
+async fn swap() {
+    let base_url = replay_server();
+
+    let quote =
+        jup_ag::quote_with_base_url(base_url, SOL, USDC, 100_000_000, QuoteConfig::default())
+            .await
+            .unwrap();
+    let swap = jup_ag::swap_with_base_url(base_url, SwapRequest::new(USER, quote))
+        .await
+        .unwrap();
+    assert_eq!(swap.swap_transaction.message.static_account_keys()[0], USER);
+    assert!(swap.estimated_fee_lamports() >= 5_000);
+    assert!(swap.writable_accounts().contains(&USER));
+}
+
+/// Each instruction's program id and accounts, with accounts loaded from address lookup tables
+/// given by their position among the loaded addresses
+#[test]
+fn unknown_prioritization_type() {
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/swap_instructions.json");
+    let mut swap_instructions =
+        serde_json::from_slice::<serde_json::Value>(&std::fs::read(fixture).unwrap()).unwrap();
+    let prioritization_type = serde_json::json!({"bundleTip": {"lamports": 1000}});
+    swap_instructions["prioritizationType"] = prioritization_type.clone();
+
+    let swap_instructions =
+        serde_json::from_value::<jup_ag::SwapInstructions>(swap_instructions).unwrap();
+    assert_eq!(
+        swap_instructions.prioritization_type,
+        Some(jup_ag::PrioritizationType::Unknown(
+            prioritization_type.clone()
+        ))
+    );
+    assert_eq!(
+        serde_json::to_value(&swap_instructions).unwrap()["prioritizationType"],
+        prioritization_type
+    );
+}
+
+fn resolved_instructions(swap: &jup_ag::Swap) -> Vec<(Pubkey, Vec<Result<Pubkey, usize>>)> {
+    let message = &swap.swap_transaction.message;
+    let account_keys = message.static_account_keys();
+    let resolve = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| index as usize - account_keys.len())
+    };
+    message
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            (
+                resolve(instruction.program_id_index).unwrap(),
+                instruction.accounts.iter().copied().map(resolve).collect(),
+            )
+        })
+        .collect()
+}
+
+fn compute_unit_price_instruction(micro_lamports: u64) -> (Pubkey, Vec<Result<Pubkey, usize>>) {
+    let instruction = ComputeBudgetInstruction::set_compute_unit_price(micro_lamports);
+    assert_eq!(instruction.data[0], 3);
+    (instruction.program_id, vec![])
+}
+
+#[tokio::test]
+async fn swap_set_compute_unit_price() {
+    let base_url = replay_server();
+
+    let quote =
+        jup_ag::quote_with_base_url(base_url, SOL, USDC, 100_000_000, QuoteConfig::default())
+            .await
+            .unwrap();
+    let swap = jup_ag::swap_with_base_url(base_url, SwapRequest::new(USER, quote))
+        .await
+        .unwrap();
+
+    // The fixture already sets a compute unit price, which is rewritten in place
+    let mut repriced = swap.clone();
+    repriced.set_compute_unit_price(12_345).unwrap();
+    assert!(repriced.swap_transaction.sanitize().is_ok());
+    assert_eq!(
+        resolved_instructions(&repriced),
+        resolved_instructions(&swap)
+    );
+    assert_eq!(
+        repriced
+            .swap_transaction
+            .message
+            .instructions()
+            .iter()
+            .filter(|instruction| instruction.data.first() == Some(&3))
+            .map(|instruction| instruction.data.clone())
+            .collect::<Vec<_>>(),
+        vec![ComputeBudgetInstruction::set_compute_unit_price(12_345).data]
+    );
+    assert!(repriced.estimated_fee_lamports() > swap.estimated_fee_lamports());
+}
+
+#[test]
+fn swap_set_compute_unit_price_inserts_program() {
+    let lookup_table = AddressLookupTableAccount {
+        key: Pubkey::new_unique(),
+        addresses: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+    };
+    let program_id = Pubkey::new_unique();
+    let message = v0::Message::try_compile(
+        &USER,
+        &[Instruction::new_with_bytes(
+            program_id,
+            &[1, 2, 3],
+            vec![
+                AccountMeta::new(USER, true),
+                AccountMeta::new(lookup_table.addresses[1], false),
+                AccountMeta::new_readonly(lookup_table.addresses[0], false),
+            ],
+        )],
+        &[lookup_table],
+        Hash::new_unique(),
+    )
+    .unwrap();
+    let mut swap = jup_ag::Swap {
+        swap_transaction: VersionedTransaction {
+            signatures: vec![Default::default()],
+            message: VersionedMessage::V0(message),
+        },
+        last_valid_block_height: 0,
+    };
+    let original = resolved_instructions(&swap);
+
+    swap.set_compute_unit_price(1_000).unwrap();
+    assert!(swap.swap_transaction.sanitize().is_ok());
+    assert_eq!(
+        resolved_instructions(&swap),
+        [vec![compute_unit_price_instruction(1_000)], original].concat()
+    );
+    assert_eq!(
+        swap.swap_transaction.message.instructions()[0].data,
+        ComputeBudgetInstruction::set_compute_unit_price(1_000).data
+    );
+
+    // A second call rewrites the inserted instruction rather than adding another
+    swap.set_compute_unit_price(2_000).unwrap();
+    assert_eq!(swap.swap_transaction.message.instructions().len(), 2);
+    assert_eq!(
+        swap.swap_transaction.message.instructions()[0].data,
+        ComputeBudgetInstruction::set_compute_unit_price(2_000).data
+    );
+}
+
+#[tokio::test]
+async fn chunked_swaps() {
+    let (base_url, requests) = fixture_server();
+    let payers = [USER, Pubkey::new_unique()];
+    let mut swap_request_builder = jup_ag::SwapRequestBuilder::new(Pubkey::new_unique());
+    swap_request_builder.wrap_and_unwrap_sol = Some(false);
+
+    let swaps = jup_ag::chunked_swaps_with_base_url(
+        &base_url,
+        SOL,
+        USDC,
+        11,
+        3,
+        QuoteConfig::default(),
+        &swap_request_builder,
+        &payers,
+    )
+    .await
+    .unwrap();
+    assert_eq!(swaps.len(), 3);
+
+    let requests = requests.try_iter().collect::<Vec<_>>();
+    assert_eq!(requests.len(), 6, "{requests:?}");
+    for (chunk, (amount, payer)) in [(3, payers[0]), (3, payers[1]), (5, payers[0])]
+        .into_iter()
+        .enumerate()
+    {
+        let (quote_target, _) = &requests[chunk * 2];
+        assert!(
+            quote_target
+                .split(['?', '&'])
+                .any(|param| param == format!("amount={amount}")),
+            "{quote_target}"
+        );
+
+        // Only the payer differs from the template
+        let (swap_target, swap_body) = &requests[chunk * 2 + 1];
+        assert_eq!(swap_target, "/swap");
+        let swap_body = serde_json::from_str::<serde_json::Value>(swap_body).unwrap();
+        assert_eq!(swap_body["userPublicKey"], payer.to_string());
+        assert_eq!(swap_body["wrapAndUnwrapSol"], false);
+    }
+}
+
+#[tokio::test]
+async fn swap_instructions() {
+    let base_url = replay_server();
+
+    let quote =
+        jup_ag::quote_with_base_url(base_url, SOL, USDC, 100_000_000, QuoteConfig::default())
+            .await
+            .unwrap();
+    let swap_instructions =
+        jup_ag::swap_instructions_with_base_url(base_url, SwapRequest::new(USER, quote))
+            .await
+            .unwrap();
+    assert!(swap_instructions
+        .swap_instruction
+        .accounts
+        .iter()
+        .any(|account| account.pubkey == USER && account.is_signer));
+
+    let round_trip = serde_json::from_str::<jup_ag::SwapInstructions>(
+        &serde_json::to_string(&swap_instructions).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        round_trip.swap_instruction,
+        swap_instructions.swap_instruction
+    );
+    assert_eq!(
+        round_trip.setup_instructions,
+        swap_instructions.setup_instructions
+    );
+}
+
+#[tokio::test]
+async fn tokens() {
+    let token_list = jup_ag::TokenList::from(
+        jup_ag::tokens_detailed_with_base_url(replay_server())
+            .await
+            .unwrap(),
+    );
+    assert_eq!(token_list.by_mint(&USDC).unwrap().decimals, 6);
+    assert_eq!(token_list.decimals(&USDC).unwrap(), 6);
+    assert!(matches!(
+        token_list.decimals(&Pubkey::new_unique()),
+        Err(jup_ag::Error::UnknownMint(_))
+    ));
+}
+
+#[tokio::test]
+async fn to_raw_amount() {
+    let token_list = jup_ag::TokenList::from(
+        jup_ag::tokens_detailed_with_base_url(replay_server())
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(token_list.to_raw_amount(&USDC, 1.5).unwrap(), 1_500_000);
+    assert_eq!(token_list.to_ui_amount(&USDC, 1_500_000).unwrap(), 1.5);
+    for ui_amount in [f64::NAN, f64::INFINITY, -1., 0., 1e14] {
+        assert!(
+            matches!(
+                token_list.to_raw_amount(&USDC, ui_amount),
+                Err(jup_ag::Error::InvalidConfig(_))
+            ),
+            "{ui_amount}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn quote_ui_rejects_invalid_amount() {
+    // Rejected before the input mint is even looked up
+    let unknown_mint = Pubkey::new_unique();
+    for ui_amount in [f64::NAN, -1., 0.] {
+        assert!(
+            matches!(
+                jup_ag::quote_ui(unknown_mint, USDC, ui_amount, QuoteConfig::default()).await,
+                Err(jup_ag::Error::InvalidConfig(_))
+            ),
+            "{ui_amount}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn quote_stream_rejects_zero_interval() {
+    use futures_util::StreamExt;
+
+    let quotes = jup_ag::quote_stream(
+        SOL,
+        USDC,
+        100_000_000,
+        QuoteConfig::default(),
+        std::time::Duration::ZERO,
+    )
+    .collect::<Vec<_>>()
+    .await;
+    assert!(matches!(
+        quotes.as_slice(),
+        [Err(jup_ag::Error::InvalidConfig(_))]
+    ));
+}
+
+#[tokio::test]
+async fn program_id_to_label() {
+    assert!(!jup_ag::program_id_to_label_with_base_url(replay_server())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn price() {
+    let price = jup_ag::price_with_base_url(replay_server(), SOL, USDC, 1.)
+        .await
+        .unwrap();
+    assert_eq!(price.input_mint, SOL);
+    assert_eq!(price.output_mint, USDC);
+
+    let round_trip =
+        serde_json::from_str::<jup_ag::Price>(&serde_json::to_string(&price).unwrap()).unwrap();
+    assert_eq!(round_trip.input_mint, price.input_mint);
+    assert_eq!(round_trip.price, price.price);
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test]
+fn price_decimal_is_exact() {
+    let json = format!(
+        r#"{{"id":"{SOL}","mintSymbol":"SOL","vsToken":"{USDC}","vsTokenSymbol":"USDC","price":0.1234567890123456789}}"#
+    );
+    let price = serde_json::from_str::<jup_ag::Price>(&json).unwrap();
+    assert_eq!(price.price, 0.12345678901234568);
+    assert_eq!(price.price_decimal.to_string(), "0.1234567890123456789");
+}
+
+#[test]
+fn quote_price_impact_pct_formats() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quote.json");
+    let mut quote =
+        serde_json::from_slice::<serde_json::Value>(&std::fs::read(fixture).unwrap()).unwrap();
+
+    for (price_impact_pct, expected) in [
+        ("1.2e-7", "0.00000012"),
+        ("1E-10", "0.0000000001"),
+        ("+0.25", "0.25"),
+        ("+1.5e+2", "150"),
+    ] {
+        quote["priceImpactPct"] = price_impact_pct.into();
+        let quote = serde_json::from_value::<jup_ag::Quote>(quote.clone())
+            .unwrap_or_else(|err| panic!("{price_impact_pct}: {err}"));
+
+        assert_eq!(quote.price_impact_pct, expected.parse::<f64>().unwrap());
+        #[cfg(feature = "rust_decimal")]
+        assert_eq!(
+            quote.price_impact_pct_decimal,
+            expected.parse::<rust_decimal::Decimal>().unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn error_sources() {
+    use {
+        base64::prelude::{Engine as _, BASE64_STANDARD},
+        std::error::Error as _,
+    };
+
+    let errors: Vec<jup_ag::Error> = vec![
+        reqwest::Client::new()
+            .get("not a url")
+            .send()
+            .await
+            .unwrap_err()
+            .into(),
+        "not a pubkey".parse::<Pubkey>().unwrap_err().into(),
+        BASE64_STANDARD.decode("!").unwrap_err().into(),
+        bincode::deserialize::<u64>(&[]).unwrap_err().into(),
+        serde_json::from_str::<u64>("x").unwrap_err().into(),
+        {
+            let task = tokio::spawn(std::future::pending::<()>());
+            task.abort();
+            task.await.unwrap_err().into()
+        },
+        solana_sdk::signer::SignerError::Custom("no signer".into()).into(),
+    ];
+    for err in errors {
+        let source = err
+            .source()
+            .unwrap_or_else(|| panic!("no source for {err:?}"));
+        assert!(err.to_string().contains(&source.to_string()), "{err}");
+    }
+
+    let err = jup_ag::Error::TransactionDecode {
+        source: Box::new(bincode::deserialize::<u64>(&[]).unwrap_err().into()),
+    };
+    let chain = std::iter::successors(Some(&err as &dyn std::error::Error), |err| (*err).source())
+        .map(|err| err.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(chain.len(), 3, "{chain:?}");
+    assert!(chain[0].contains(&chain[1]));
+    assert!(chain[1].contains(&chain[2]));
+}
+
+#[test]
+fn quote_amount_overflow() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quote.json");
+    let quote =
+        serde_json::from_slice::<serde_json::Value>(&std::fs::read(fixture).unwrap()).unwrap();
+    let over_max = "18446744073709551616";
+
+    for field in ["inAmount", "outAmount"] {
+        let mut quote = quote.clone();
+        quote[field] = over_max.into();
+        let err = serde_json::from_value::<jup_ag::Quote>(quote).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("Parse error for `{over_max}`: number too large")),
+            "{field}: {err}"
+        );
+    }
+
+    // A JSON number arrives rounded through `f64`, but fails with the same overflow error
+    for fee_bps in [
+        serde_json::Value::from(over_max),
+        serde_json::from_str(over_max).unwrap(),
+    ] {
+        let mut quote = quote.clone();
+        quote["platformFee"] = serde_json::json!({"amount": "0", "feeBps": fee_bps});
+        let err = serde_json::from_value::<jup_ag::Quote>(quote).unwrap_err();
+        assert!(
+            err.to_string().contains("number too large"),
+            "{fee_bps}: {err}"
+        );
+    }
+}
+
+fn fixture_quote() -> jup_ag::Quote {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quote.json");
+    serde_json::from_slice(&std::fs::read(fixture).unwrap()).unwrap()
+}
+
+/// A swap whose only instruction is the Jupiter route instruction `name`, with `mints` at the
+/// given account positions, ending its data with `tail`. Accounts in `lookup_table` are loaded
+/// from an address lookup table.
+fn route_swap(
+    name: &str,
+    mints: &[(usize, Pubkey)],
+    tail: &[u8],
+    lookup_table: &[Pubkey],
+) -> jup_ag::Swap {
+    let mut accounts = (0..13)
+        .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+        .collect::<Vec<_>>();
+    accounts[1] = AccountMeta::new_readonly(USER, true);
+    for (position, mint) in mints {
+        accounts[*position].pubkey = *mint;
+    }
+
+    let data = [
+        &solana_sdk::hash::hashv(&[format!("global:{name}").as_bytes()]).to_bytes()[..8],
+        // Route plan id and an empty route plan
+        &[0, 0, 0, 0, 0],
+        tail,
+    ]
+    .concat();
+    let message = v0::Message::try_compile(
+        &USER,
+        &[Instruction::new_with_bytes(
+            pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"),
+            &data,
+            accounts,
+        )],
+        &[AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: lookup_table.to_vec(),
+        }],
+        Hash::new_unique(),
+    )
+    .unwrap();
+    jup_ag::Swap {
+        swap_transaction: VersionedTransaction {
+            signatures: vec![Default::default()],
+            message: VersionedMessage::V0(message),
+        },
+        last_valid_block_height: 0,
+    }
+}
+
+#[test]
+fn swap_serialized_size_with() {
+    let transaction = |instructions: &[Instruction]| {
+        let message =
+            v0::Message::try_compile(&USER, instructions, &[], Hash::new_unique()).unwrap();
+        VersionedTransaction {
+            signatures: vec![Default::default(); message.header.num_required_signatures as usize],
+            message: VersionedMessage::V0(message),
+        }
+    };
+    let route = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1, 2, 3],
+        vec![
+            AccountMeta::new(USER, true),
+            AccountMeta::new_readonly(SOL, false),
+        ],
+    );
+    let swap = jup_ag::Swap {
+        swap_transaction: transaction(std::slice::from_ref(&route)),
+        last_valid_block_height: 0,
+    };
+
+    // A new signer, an existing account becoming a signer, and a new non-signer account
+    let extra_instructions = [Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[4],
+        vec![
+            AccountMeta::new(Pubkey::new_unique(), true),
+            AccountMeta::new_readonly(SOL, true),
+            AccountMeta::new_readonly(USDC, false),
+        ],
+    )];
+    let extended = transaction(&[&[route][..], &extra_instructions].concat());
+    assert_eq!(extended.signatures.len(), 3);
+    assert_eq!(
+        swap.serialized_size_with(&extra_instructions).unwrap(),
+        bincode::serialized_size(&extended).unwrap() as usize
+    );
+}
+
+#[test]
+fn swap_partial_sign() {
+    use solana_sdk::signature::{Keypair, Signer};
+
+    let payer = Keypair::new();
+    let cosigner = Keypair::new();
+    let message = v0::Message::try_compile(
+        &payer.pubkey(),
+        &[Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new_readonly(cosigner.pubkey(), true)],
+        )],
+        &[],
+        Hash::default(),
+    )
+    .unwrap();
+    let mut swap = jup_ag::Swap {
+        swap_transaction: VersionedTransaction {
+            signatures: vec![],
+            message: VersionedMessage::V0(message),
+        },
+        last_valid_block_height: 0,
+    };
+    let blockhash = Hash::new_unique();
+
+    // Each party signs in its own call
+    swap.partial_sign(&[&payer], blockhash).unwrap();
+    assert!(!swap.is_fully_signed());
+    swap.partial_sign(&[&cosigner], blockhash).unwrap();
+    assert!(swap.is_fully_signed());
+    assert_eq!(
+        swap.swap_transaction.verify_with_results(),
+        vec![true, true]
+    );
+
+    assert!(matches!(
+        swap.partial_sign(&[&Keypair::new()], blockhash),
+        Err(jup_ag::Error::InvalidTransaction(_))
+    ));
+
+    // A new blockhash invalidates the payer's signature, so it is cleared
+    swap.partial_sign(&[&cosigner], Hash::new_unique()).unwrap();
+    assert!(!swap.is_fully_signed());
+    assert_eq!(swap.swap_transaction.signatures[0], Default::default());
+    assert_eq!(
+        swap.swap_transaction.verify_with_results(),
+        vec![false, true]
+    );
+}
+
+/// Route instruction tail with the fixed amount, quoted amount, slippage and platform fee
+fn route_tail(amount: Option<u64>, quoted_amount: u64, slippage_bps: u16) -> Vec<u8> {
+    [
+        amount
+            .map(u64::to_le_bytes)
+            .as_ref()
+            .map_or(&[][..], |amount| &amount[..]),
+        &quoted_amount.to_le_bytes(),
+        &slippage_bps.to_le_bytes(),
+        &[0],
+    ]
+    .concat()
+}
+
+#[test]
+fn swap_matches_quote() {
+    let quote = fixture_quote();
+    let tail = route_tail(Some(quote.in_amount), quote.out_amount, 50);
+    let shared_mints = [(7, SOL), (8, USDC)];
+
+    let swap = route_swap("shared_accounts_route", &shared_mints, &tail, &[]);
+    assert!(swap.matches_quote(&quote));
+
+    // A different output mint with the same amounts
+    let tampered = route_swap("shared_accounts_route", &[(7, SOL), (8, USER)], &tail, &[]);
+    assert!(!tampered.matches_quote(&quote));
+
+    // A different amount with the same mints
+    let tampered = route_swap(
+        "shared_accounts_route",
+        &shared_mints,
+        &route_tail(Some(quote.in_amount * 2), quote.out_amount, 50),
+        &[],
+    );
+    assert!(!tampered.matches_quote(&quote));
+
+    // Mints loaded from an address lookup table cannot be checked
+    let looked_up = route_swap("shared_accounts_route", &shared_mints, &tail, &[USDC]);
+    assert!(!looked_up.matches_quote(&quote));
+
+    // `route` has no source mint account
+    let route = route_swap("route", &[(5, USDC)], &tail, &[]);
+    assert!(!route.matches_quote(&quote));
+
+    let exact_out = route_swap("exact_out_route", &[(5, SOL), (6, USDC)], &tail, &[]);
+    assert!(!exact_out.matches_quote(&quote));
+    let mut exact_out_quote = quote.clone();
+    exact_out_quote.swap_mode = "ExactOut".into();
+    std::mem::swap(
+        &mut exact_out_quote.in_amount,
+        &mut exact_out_quote.out_amount,
+    );
+    assert!(exact_out.matches_quote(&exact_out_quote));
+}
+
+#[test]
+fn swap_matches_quote_with_token_ledger() {
+    let quote = fixture_quote();
+    let swap = route_swap(
+        "shared_accounts_route_with_token_ledger",
+        &[(7, SOL), (8, USDC)],
+        &route_tail(None, quote.out_amount, 50),
+        &[],
+    );
+    assert!(swap.matches_quote(&quote));
+
+    let tampered = route_swap(
+        "shared_accounts_route_with_token_ledger",
+        &[(7, SOL), (8, USDC)],
+        &route_tail(None, quote.out_amount + 1, 50),
+        &[],
+    );
+    assert!(!tampered.matches_quote(&quote));
+}
+
+#[test]
+fn swap_matches_request_with_dynamic_slippage() {
+    let quote = fixture_quote();
+    let swap = |slippage_bps| {
+        route_swap(
+            "shared_accounts_route",
+            &[(7, SOL), (8, USDC)],
+            &route_tail(Some(quote.in_amount), quote.out_amount, slippage_bps),
+            &[],
+        )
+    };
+    let request = SwapRequest::new(USER, quote.clone())
+        .dynamic_slippage_capped_at(300)
+        .unwrap();
+
+    assert!(!swap(120).matches_quote(&quote));
+    assert!(swap(120).matches_request(&request));
+    assert!(!swap(301).matches_request(&request));
+    assert!(swap(50).matches_request(&SwapRequest::new(USER, quote.clone())));
+    assert!(!swap(120).matches_request(&SwapRequest::new(USER, quote.clone())));
+}
+
+#[tokio::test]
+async fn quote_url_with_dexes_and_restrict_intermediate_tokens() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quote.json");
+    let (base_url, target) = serve_once(std::fs::read_to_string(fixture).unwrap());
+
+    jup_ag::quote_with_base_url(
+        &base_url,
+        SOL,
+        USDC,
+        100_000_000,
+        QuoteConfig {
+            dexes: Some(vec!["Orca V2".into(), "Raydium".into()]),
+            restrict_intermediate_tokens: Some(true),
+            ..QuoteConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        target.recv().unwrap(),
+        format!(
+            "/quote?inputMint={SOL}&outputMint={USDC}&amount=100000000\
+             &dexes=Orca%20V2,Raydium&restrictIntermediateTokens=true"
+        )
+    );
+}
+
+#[tokio::test]
+async fn split_swap_rejects_invalid_weights() {
+    for outputs in [
+        vec![(USDC, f64::NAN)],
+        vec![(USDC, f64::NAN), (SOL, 1.)],
+        vec![(USDC, f64::INFINITY), (SOL, f64::NEG_INFINITY)],
+        vec![(USDC, -0.5), (SOL, 1.5)],
+        vec![(USDC, 0.), (SOL, 1.)],
+        // Rounds down to a zero amount
+        vec![(USDC, 1e-9), (SOL, 1. - 1e-9)],
+        vec![(USDC, 0.5)],
+        vec![],
+    ] {
+        match jup_ag::split_swap(SOL, &outputs, 100_000_000, QuoteConfig::default()).await {
+            Err(jup_ag::Error::InvalidConfig(_)) => {}
+            result => panic!("{outputs:?}: unexpected {result:?}"),
+        }
+    }
+}